@@ -1,16 +1,121 @@
 use std::fmt;
 
-pub enum Expr {
-    Num(f64),
+use crate::Span;
+
+/// A single parsed line of input
+pub enum Statement {
+	/// Plain expression to be evaluated
+	Expr(Box<Expr>),
+	/// Variable assignment, `name = expr`
+	Assign(String, Box<Expr>),
+	/// Compound assignment applying an operation to a variable, `name += expr`,
+	/// along with the span of the variable name
+	CompoundAssign(String, Span, Operation, Box<Expr>),
+	/// Function definition, `name(params, ...) = expr`
+	FuncDef(String, Vec<String>, Box<Expr>),
+}
+
+/// Node of the syntax tree, along with the part of the input it was parsed from
+#[derive(Clone)]
+pub struct Expr {
+	pub kind: ExprKind,
+	pub span: Span,
+}
+
+#[derive(Clone)]
+pub enum ExprKind {
+    /// Number literal, kept as written so it can be parsed according to the
+    /// number mode
+    Num(String),
     Op(Box<Expr>, Operation, Box<Expr>),
-	Func(String, Vec<Box<Expr>>),
+	Func(String, Vec<Expr>),
 	Var(String),
 	Fac(Box<Expr>),
+	Neg(Box<Expr>),
+	/// Logical not, `!a`, which is 1 if `a` is 0 and 0 otherwise
+	Not(Box<Expr>),
+	/// Bitwise not of an integer, `~a`
+	BitNot(Box<Expr>),
+	/// Angle in degrees, converted to the angle mode when evaluated
+	Degrees(Box<Expr>),
+	/// Conversion of a quantity to a unit, `value to unit`
+	Convert(Box<Expr>, Box<Expr>),
+	/// List literal, `[a, b, c]`
+	List(Vec<Expr>),
+	/// Element of a list, `list[index]`
+	Index(Box<Expr>, Box<Expr>),
+	/// Part of a list, `list[start:end]` where both bounds are optional
+	Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+	/// Conditional, made of pairs of conditions and values along with a
+	/// default value. Only the value of the first condition that holds is
+	/// evaluated. Written as `piecewise(c1, a, c2, b, default)`, or as
+	/// `if(c, a, b)` and `c ? a : b` when there's a single condition
+	Piecewise(Vec<(Expr, Expr)>, Option<Box<Expr>>),
+}
+impl Expr {
+	/// Builds a node spanning the input from byte `start` up to byte `end`
+	pub fn new(kind: ExprKind, start: usize, end: usize) -> Box<Expr> {
+		Box::new(Expr { kind, span: Span { start, end } })
+	}
+
+	/// Builds the implicit multiplication `lhs rhs`, rejecting names right after
+	/// a degree sign, so that `10 °C` isn't read as 10 degrees times a coulomb
+	pub fn implicit_mul(lhs: Box<Expr>, rhs: Box<Expr>, start: usize, end: usize) -> Result<Box<Expr>, ActionError> {
+		if matches!(rhs.kind, ExprKind::Var(_)) && lhs.ends_with_degrees() {
+			return Err(ActionError { location: rhs.span.start, msg: "Names can't follow a degree sign, temperatures like °C aren't supported" });
+		}
+		Ok(Expr::new(ExprKind::Op(lhs, Operation::Mul, rhs), start, end))
+	}
+
+	/// Returns true if the last factor of the expression is an angle in degrees
+	fn ends_with_degrees(&self) -> bool {
+		match &self.kind {
+			ExprKind::Degrees(_) => true,
+			ExprKind::Op(_, Operation::Mul, rhs) => rhs.ends_with_degrees(),
+			_ => false,
+		}
+	}
+
+	/// Builds a function call, turning calls of `if()` and `piecewise()` into
+	/// conditionals so that their arguments are evaluated lazily
+	pub fn call(name: String, mut args: Vec<Expr>, start: usize, end: usize) -> Result<Box<Expr>, ActionError> {
+		match name.as_str() {
+			"if" => {
+				if args.len() != 3 {
+					return Err(ActionError { location: start, msg: "\"if()\" takes a condition and two values" });
+				}
+				let default = args.pop().map(Box::new);
+				let value = args.pop().expect("Three arguments");
+				let cond = args.pop().expect("Three arguments");
+				Ok(Expr::new(ExprKind::Piecewise(vec![(cond, value)], default), start, end))
+			}
+			"piecewise" => {
+				if args.len() < 2 {
+					return Err(ActionError { location: start, msg: "\"piecewise()\" takes at least a condition and a value" });
+				}
+				// an odd number of arguments ends with the default value
+				let default = (args.len() % 2 == 1).then(|| Box::new(args.pop().expect("Odd number of arguments")));
+				let mut cases = Vec::with_capacity(args.len() / 2);
+				let mut args = args.into_iter();
+				while let (Some(cond), Some(value)) = (args.next(), args.next()) {
+					cases.push((cond, value));
+				}
+				Ok(Expr::new(ExprKind::Piecewise(cases, default), start, end))
+			}
+			_ => Ok(Expr::new(ExprKind::Func(name, args), start, end)),
+		}
+	}
 }
 impl fmt::Display for Expr {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "{}", self.kind)
+	}
+}
+impl fmt::Display for ExprKind {
 	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
 		let s = match self {
-			Self::Num(n) => n.to_string(),
+			Self::Num(n) => n.clone(),
+			Self::Op(lhs, Operation::BitXor, rhs) => format!("({lhs} xor {rhs})"),
 			Self::Op(lhs, op, rhs) => format!("({lhs}{op}{rhs})"),
 			Self::Func(name, args) => {
 				let mut arg_list = String::new();
@@ -21,13 +126,33 @@ impl fmt::Display for Expr {
 				arg_list.pop();
 				format!("{name}({arg_list})")
 			},
-			Self::Var(name) => format!("{name}"),
+			Self::List(items) => {
+				let items: Vec<String> = items.iter().map(Expr::to_string).collect();
+				format!("[{}]", items.join(","))
+			},
+			Self::Index(list, i) => format!("{list}[{i}]"),
+			Self::Slice(list, start, end) => {
+				let bound = |b: &Option<Box<Expr>>| b.as_ref().map(|e| e.to_string()).unwrap_or_default();
+				format!("{list}[{}:{}]", bound(start), bound(end))
+			},
+			Self::Var(name) => name.clone(),
 			Self::Fac(n) => format!("{n}!"),
+			Self::Neg(n) => format!("(-{n})"),
+			Self::Not(n) => format!("(!{n})"),
+			Self::BitNot(n) => format!("(~{n})"),
+			Self::Degrees(n) => format!("{n}°"),
+			Self::Convert(n, unit) => format!("({n} to {unit})"),
+			Self::Piecewise(cases, default) => {
+				let mut args: Vec<String> = cases.iter().flat_map(|(cond, value)| [cond.to_string(), value.to_string()]).collect();
+				args.extend(default.iter().map(|e| e.to_string()));
+				format!("piecewise({})", args.join(","))
+			},
 		};
 		write!(formatter, "{}", s)
 	}
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum Operation {
 	Add,
     Sub,
@@ -36,6 +161,31 @@ pub enum Operation {
 	FloorDiv,
 	Mod,
 	Exp,
+	Lt,
+	Le,
+	Gt,
+	Ge,
+	Eq,
+	Ne,
+	And,
+	Or,
+	BitAnd,
+	BitOr,
+	BitXor,
+	Shl,
+	Shr,
+}
+impl Operation {
+	/// Returns true for comparisons and logical operations, which evaluate to
+	/// 1 if true and 0 if false
+	pub fn is_logical(self) -> bool {
+		matches!(self, Self::Lt | Self::Le | Self::Gt | Self::Ge | Self::Eq | Self::Ne | Self::And | Self::Or)
+	}
+
+	/// Returns true for bitwise operations, which only take integers
+	pub fn is_bitwise(self) -> bool {
+		matches!(self, Self::BitAnd | Self::BitOr | Self::BitXor | Self::Shl | Self::Shr)
+	}
 }
 impl fmt::Display for Operation {
 	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -47,7 +197,27 @@ impl fmt::Display for Operation {
 			Self::FloorDiv => "//",
 			Self::Mod => "%",
 			Self::Exp => "^",
+			Self::Lt => "<",
+			Self::Le => "<=",
+			Self::Gt => ">",
+			Self::Ge => ">=",
+			Self::Eq => "==",
+			Self::Ne => "!=",
+			Self::And => "&&",
+			Self::Or => "||",
+			Self::BitAnd => "&",
+			Self::BitOr => "|",
+			Self::BitXor => "xor",
+			Self::Shl => "<<",
+			Self::Shr => ">>",
 		};
 		write!(formatter, "{}", s)
 	}
-}
\ No newline at end of file
+}
+/// Error raised from within a grammar action, carrying the location of the
+/// offending token so it can be reported like any other parser error
+#[derive(Debug)]
+pub struct ActionError {
+	pub location: usize,
+	pub msg: &'static str,
+}
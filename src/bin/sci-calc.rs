@@ -3,7 +3,7 @@ use std::env;
 use rustyline::{history::History, DefaultEditor};
 use ansi_term::Color::Red;
 
-use sci_calc::{calculate, context::Context};
+use sci_calc::{calculate_script, Answer, context::{Context, AngleMode, NumberMode, OverflowMode}, value::Value};
 
 fn main() {
 
@@ -17,10 +17,7 @@ fn main() {
 			input.push(' ');
 		}
 		println!("{input}");
-		match calculate(input.as_str(), &mut ctx) {
-			Ok(result) => println!(" = {result}"),
-			Err(e) => println!("{}", Red.paint(e.to_string())),
-		}
+		run(input.as_str(), &mut ctx);
 		return;
 	}
 
@@ -28,24 +25,167 @@ fn main() {
 	let mut rl = DefaultEditor::new().unwrap();
 	loop {
 		let readline = rl.readline("");
-		if let Err(_) = &readline {
+		if readline.is_err() {
 			break;
 		}
 		let input = readline.unwrap();
 		let input = input.as_str();
 		if input.eq_ignore_ascii_case("exit") { break; }
-		if input.eq("") { continue; }
-
-		match calculate(input, &mut ctx) {
-			Ok(result) => {
-				println!(" = {result}");
+		if input.is_empty() { continue; }
+		if let Some(args) = input.strip_prefix("angle") {
+			// `angle = 3` is still an assignment
+			if args.is_empty() || args.starts_with(' ') && args.trim().chars().all(|c| c.is_ascii_alphabetic()) {
+				angle_command(args.trim(), &mut ctx);
 				rl.history_mut().add(input).expect("Failed to append to history");
+				continue;
 			}
-			Err(e) => { 
-				println!("{}", Red.paint(e.to_string()));
+		}
+		if let Some(args) = input.strip_prefix("strict") {
+			// `strict = 3` is still an assignment
+			if args.is_empty() || args.starts_with(' ') && args.trim().chars().all(|c| c.is_ascii_alphabetic()) {
+				strict_command(args.trim(), &mut ctx);
+				rl.history_mut().add(input).expect("Failed to append to history");
+				continue;
+			}
+		}
+		if let Some(args) = input.strip_prefix("mode") {
+			// `mode = 3` is still an assignment
+			if args.is_empty() || args.starts_with(' ') && args.trim().chars().all(|c| c.is_ascii_alphanumeric() || c == ' ') {
+				mode_command(args.trim(), &mut ctx);
+				rl.history_mut().add(input).expect("Failed to append to history");
+				continue;
 			}
 		}
+
+		if run(input, &mut ctx) {
+			rl.history_mut().add(input).expect("Failed to append to history");
+		}
 	}
 
 	// bye bye
-}
\ No newline at end of file
+}
+
+/// Runs every statement of the input, printing their answers up to the first
+/// error. Returns true if all of them succeeded
+fn run(input: &str, ctx: &mut Context) -> bool {
+	match calculate_script(input, ctx) {
+		Ok(answers) => {
+			answers.into_iter().for_each(|answer| print_answer(answer, ctx));
+			true
+		}
+		Err(e) => {
+			e.answers.into_iter().for_each(|answer| print_answer(answer, ctx));
+			println!("{}", Red.paint(e.error.to_string()));
+			false
+		}
+	}
+}
+
+fn print_answer(answer: Answer, ctx: &Context) {
+	match answer {
+		Answer::Value(Value::Integer(i)) if ctx.number_mode == NumberMode::Programmer => {
+			// integers are shown in every base, negative ones as their bits
+			let bits = ctx.int_type.bit_pattern(&i);
+			println!(" = {i}");
+			println!("   hex {bits:X}");
+			println!("   dec {i}");
+			println!("   oct {bits:o}");
+			println!("   bin {}", group_bits(&format!("{bits:b}")));
+		}
+		Answer::Value(result) => println!(" = {}", result.format(ctx)),
+		Answer::Definition(signature) => println!(" defined {signature}"),
+	}
+}
+
+/// Shows the angle mode, or changes it with `angle rad`, `angle deg` or
+/// `angle grad`
+fn angle_command(arg: &str, ctx: &mut Context) {
+	ctx.angle_mode = match arg {
+		"" => {
+			println!(" angle mode is {}", ctx.angle_mode);
+			return;
+		}
+		"rad" | "radians" => AngleMode::Radians,
+		"deg" | "degrees" => AngleMode::Degrees,
+		"grad" | "gradians" => AngleMode::Gradians,
+		_ => {
+			println!("{}", Red.paint(format!("Unknown angle mode \"{arg}\", expected rad, deg or grad")));
+			return;
+		}
+	};
+	println!(" angle mode set to {}", ctx.angle_mode);
+}
+
+/// Shows whether strict numerics are enabled, or changes it with `strict on`
+/// or `strict off`
+fn strict_command(arg: &str, ctx: &mut Context) {
+	ctx.strict_numerics = match arg {
+		"" => {
+			println!(" strict numerics are {}", if ctx.strict_numerics { "on" } else { "off" });
+			return;
+		}
+		"on" => true,
+		"off" => false,
+		_ => {
+			println!("{}", Red.paint(format!("Unknown option \"{arg}\", expected on or off")));
+			return;
+		}
+	};
+	println!(" strict numerics turned {arg}");
+}
+
+/// Shows the number mode, or changes it with `mode real`, `mode complex`,
+/// `mode decimal` or `mode rational`. Programmer mode is chosen with an integer
+/// type such as `mode u8`, optionally followed by `wrap` or `checked` to pick
+/// what happens on overflow
+fn mode_command(args: &str, ctx: &mut Context) {
+	let mut args = args.split_whitespace();
+	let (mode, overflow) = (args.next(), args.next());
+	ctx.number_mode = match mode {
+		None => {
+			println!(" number mode is {}", describe_mode(ctx));
+			return;
+		}
+		Some("real") => NumberMode::Real,
+		Some("complex") => NumberMode::Complex,
+		Some("decimal") => NumberMode::Decimal,
+		Some("rational") => NumberMode::Rational,
+		Some(int_type) => {
+			let int_type = match int_type.parse() {
+				Ok(int_type) => int_type,
+				Err(_) => {
+					println!("{}", Red.paint(format!("Unknown number mode \"{int_type}\", expected real, complex, decimal, rational or an integer type such as u8")));
+					return;
+				}
+			};
+			ctx.overflow_mode = match overflow {
+				None => ctx.overflow_mode,
+				Some("wrap") => OverflowMode::Wrap,
+				Some("checked") => OverflowMode::Error,
+				Some(overflow) => {
+					println!("{}", Red.paint(format!("Unknown overflow mode \"{overflow}\", expected wrap or checked")));
+					return;
+				}
+			};
+			ctx.int_type = int_type;
+			NumberMode::Programmer
+		}
+	};
+	println!(" number mode set to {}", describe_mode(ctx));
+}
+
+/// Name of the number mode, along with the integer type in programmer mode
+fn describe_mode(ctx: &Context) -> String {
+	match ctx.number_mode {
+		NumberMode::Programmer => format!("{} ({}, {})", ctx.number_mode, ctx.int_type, ctx.overflow_mode),
+		mode => mode.to_string(),
+	}
+}
+
+/// Splits binary digits into groups of four, padding the first group with
+/// zeros, so `101101` becomes `0010 1101`
+fn group_bits(digits: &str) -> String {
+	let padded = format!("{digits:0>width$}", width = digits.len().div_ceil(4) * 4);
+	let groups: Vec<&str> = padded.as_bytes().chunks(4).map(|group| std::str::from_utf8(group).expect("Binary digits are ASCII")).collect();
+	groups.join(" ")
+}
@@ -1,36 +1,43 @@
 
 use libm::tgamma;
+use num_complex::Complex64;
+use bigdecimal::{BigDecimal, RoundingMode};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, Zero};
 
-use super::{VarTableEntry, Function};
+use super::{VarTableEntry, Function, Arity, AngleUse};
+use crate::{CalcError, CalcErrorType};
+use crate::value::{Value, complex_gamma, complex_pow, decimal, rational, list, matrix};
 
 /// Constructor function for built in constants
 pub fn get_consts() -> Vec<VarTableEntry> {
 	vec![
 		VarTableEntry {
 			name: "pi".to_string(),
-			value: std::f64::consts::PI,
+			value: Value::Real(std::f64::consts::PI),
 			constant: true,
 		},
 		VarTableEntry {
 			name: "e".to_string(),
-			value: std::f64::consts::E,
+			value: Value::Real(std::f64::consts::E),
 			constant: true,
 		},
 		// Placeholder variable to prevent assignment, `ans` is manually
 		// evaluated elsewhere
 		VarTableEntry {
 			name: "ans".to_string(),
-			value: 0.0,
+			value: Value::Real(0.0),
 			constant: true,
 		},
 		VarTableEntry {
 			name: "inf".to_string(),
-			value: std::f64::INFINITY,
+			value: Value::Real(f64::INFINITY),
 			constant: true,
 		},
 		VarTableEntry {
 			name: "nan".to_string(),
-			value: std::f64::NAN,
+			value: Value::Real(f64::NAN),
 			constant: true,
 		}
 	]
@@ -41,151 +48,519 @@ pub fn get_functions() -> Vec<Function> {
 	vec![
 		Function {
 			name: String::from("sqrt"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::sqrt(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::sqrt(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].sqrt()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::sqrt(&args[0], prec) })),
+			rational_closure: Some(Box::new(|args| { rational::sqrt(&args[0]) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("root"),
-			num_args: 2,
-			closure: Box::new(|args| { Ok(f64::powf(args[0], 1.0 / args[1])) })
+			arity: Arity::Exact(2),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::powf(args[0], 1.0 / args[1])) }),
+			complex_closure: Some(Box::new(|args| { Ok(complex_pow(args[0], 1.0 / args[1])) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::root(&args[0], &args[1], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("fac"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(tgamma(args[0] + 1.0)) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(tgamma(args[0] + 1.0)) }),
+			complex_closure: Some(Box::new(|args| { Ok(complex_gamma(args[0] + 1.0)) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::factorial(&args[0], prec) })),
+			rational_closure: Some(Box::new(|args| { rational::factorial(&args[0]) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("mean"),
-			num_args: 0,
+			arity: Arity::AtLeast(1),
+			builtin: true,
 			closure: Box::new(|args| { 
 				let mut sum = 0.0;
 				for arg in args.iter() {
 					sum += arg;
 				}
 				Ok(sum / (args.len() as f64))
-			})
+			}),
+			complex_closure: Some(Box::new(|args| {
+				let sum: Complex64 = args.iter().sum();
+				Ok(sum / (args.len() as f64))
+			})),
+			decimal_closure: Some(Box::new(|args, prec| {
+				let sum: BigDecimal = args.iter().sum();
+				decimal::div(&sum, &BigDecimal::from(args.len() as u64), prec)
+			})),
+			rational_closure: Some(Box::new(|args| {
+				let sum: BigRational = args.iter().sum();
+				Some(sum / BigInt::from(args.len()))
+			})),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("stddev"),
-			num_args: 0,
+			arity: Arity::AtLeast(1),
+			builtin: true,
 			closure: Box::new(|args| { 
 				let n = args.len() as f64;
 				let mut sum = 0.0;
 				for arg in args.iter() {
 					sum += arg;
 				}
-				let mean = sum.div_euclid(n);
+				let mean = sum / n;
 				let mut dividend = 0.0;
 				for arg in args.iter() {
 					dividend += (arg - mean).powf(2.0);
 				}
 				Ok(f64::sqrt(dividend / n))
-			})
+			}),
+			complex_closure: Some(Box::new(|args| {
+				let n = args.len() as f64;
+				let mean = args.iter().sum::<Complex64>() / n;
+				let dividend: f64 = args.iter().map(|arg| (arg - mean).norm_sqr()).sum();
+				Ok(Complex64::from(f64::sqrt(dividend / n)))
+			})),
+			decimal_closure: Some(Box::new(|args, prec| {
+				let n = BigDecimal::from(args.len() as u64);
+				let mean = decimal::div(&args.iter().sum(), &n, prec)?;
+				let dividend: BigDecimal = args.iter().map(|arg| (arg - &mean).square()).sum();
+				decimal::sqrt(&decimal::div(&dividend, &n, prec)?, prec)
+			})),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("sum"),
+			arity: Arity::AtLeast(0),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args.iter().sum()) }),
+			complex_closure: Some(Box::new(|args| { Ok(args.iter().sum()) })),
+			decimal_closure: Some(Box::new(|args, _| { Ok(args.iter().sum()) })),
+			rational_closure: Some(Box::new(|args| { Some(args.iter().sum()) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("len"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { list::len(vec![Value::Real(args[0])]).map(|len| len.to_f64().unwrap_or(f64::NAN)) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(list::len)),
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("min"),
-			num_args: 2,
-			closure: Box::new(|args| { Ok(f64::min(args[0], args[1])) })
+			arity: Arity::Exact(2),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::min(args[0], args[1])) }),
+			complex_closure: None,
+			decimal_closure: Some(Box::new(|args, _| { Ok(args[0].clone().min(args[1].clone())) })),
+			rational_closure: Some(Box::new(|args| { Some(args[0].clone().min(args[1].clone())) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("max"),
-			num_args: 2,
-			closure: Box::new(|args| { Ok(f64::max(args[0], args[1])) })
+			arity: Arity::Exact(2),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::max(args[0], args[1])) }),
+			complex_closure: None,
+			decimal_closure: Some(Box::new(|args, _| { Ok(args[0].clone().max(args[1].clone())) })),
+			rational_closure: Some(Box::new(|args| { Some(args[0].clone().max(args[1].clone())) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("abs"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(args[0].abs()) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0].abs()) }),
+			complex_closure: Some(Box::new(|args| { Ok(Complex64::from(args[0].norm())) })),
+			decimal_closure: Some(Box::new(|args, _| { Ok(args[0].abs()) })),
+			rational_closure: Some(Box::new(|args| { Some(args[0].abs()) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("round"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(args[0].round()) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0].round()) }),
+			complex_closure: Some(Box::new(|args| { Ok(Complex64::new(args[0].re.round(), args[0].im.round())) })),
+			decimal_closure: Some(Box::new(|args, _| { Ok(args[0].with_scale_round(0, RoundingMode::HalfUp)) })),
+			rational_closure: Some(Box::new(|args| { Some(args[0].round()) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("floor"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(args[0].floor()) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0].floor()) }),
+			complex_closure: Some(Box::new(|args| { Ok(Complex64::new(args[0].re.floor(), args[0].im.floor())) })),
+			decimal_closure: Some(Box::new(|args, _| { Ok(args[0].with_scale_round(0, RoundingMode::Floor)) })),
+			rational_closure: Some(Box::new(|args| { Some(args[0].floor()) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("ceil"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(args[0].ceil()) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0].ceil()) }),
+			complex_closure: Some(Box::new(|args| { Ok(Complex64::new(args[0].re.ceil(), args[0].im.ceil())) })),
+			decimal_closure: Some(Box::new(|args, _| { Ok(args[0].with_scale_round(0, RoundingMode::Ceiling)) })),
+			rational_closure: Some(Box::new(|args| { Some(args[0].ceil()) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("ln"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::log(args[0], std::f64::consts::E)) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::log(args[0], std::f64::consts::E)) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].ln()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::ln(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("log10"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::log(args[0], 10.0)) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::log(args[0], 10.0)) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].log10()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::log(&args[0], &BigDecimal::from(10), prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("log"),
-			num_args: 2,
-			closure: Box::new(|args| { Ok(f64::log(args[0], args[1])) })
+			arity: Arity::Exact(2),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::log(args[0], args[1])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].ln() / args[1].ln()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::log(&args[0], &args[1], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("sin"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::sin(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::sin(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].sin()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::sin(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::Argument,
 		},
 		Function {
 			name: String::from("cos"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::cos(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::cos(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].cos()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::cos(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::Argument,
 		},
 		Function {
 			name: String::from("tan"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::tan(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::tan(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].tan()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::tan(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::Argument,
 		},
 		Function {
 			name: String::from("sinh"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::sinh(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::sinh(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].sinh()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::sinh(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("cosh"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::cosh(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::cosh(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].cosh()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::cosh(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("tanh"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::tanh(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::tanh(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].tanh()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::tanh(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("asin"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::asin(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::asin(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].asin()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::asin(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::Result,
 		},
 		Function {
 			name: String::from("acos"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::acos(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::acos(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].acos()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::acos(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::Result,
 		},
 		Function {
 			name: String::from("atan"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::atan(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::atan(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].atan()) })),
+			decimal_closure: Some(Box::new(|args, prec| { Ok(decimal::atan(&args[0], prec)) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::Result,
 		},
 		Function {
 			name: String::from("asinh"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::asinh(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::asinh(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].asinh()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::asinh(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("acosh"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::acosh(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::acosh(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].acosh()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::acosh(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
 		},
 		Function {
 			name: String::from("atanh"),
-			num_args: 1,
-			closure: Box::new(|args| { Ok(f64::atanh(args[0])) })
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::atanh(args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].atanh()) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::atanh(&args[0], prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("deg"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0].to_degrees()) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0] * (180.0 / std::f64::consts::PI)) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::div(&(&args[0] * BigDecimal::from(180)), &decimal::pi(prec + decimal::GUARD_DIGITS), prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("rad"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0].to_radians()) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0] * (std::f64::consts::PI / 180.0)) })),
+			decimal_closure: Some(Box::new(|args, prec| { decimal::div(&(&args[0] * decimal::pi(prec + decimal::GUARD_DIGITS)), &BigDecimal::from(180), prec) })),
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("re"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0]) }),
+			complex_closure: Some(Box::new(|args| { Ok(Complex64::from(args[0].re)) })),
+			decimal_closure: Some(Box::new(|args, _| { Ok(args[0].clone()) })),
+			rational_closure: Some(Box::new(|args| { Some(args[0].clone()) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("im"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|_| { Ok(0.0) }),
+			complex_closure: Some(Box::new(|args| { Ok(Complex64::from(args[0].im)) })),
+			decimal_closure: Some(Box::new(|_, _| { Ok(BigDecimal::zero()) })),
+			rational_closure: Some(Box::new(|_| { Some(BigRational::zero()) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("arg"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(f64::atan2(0.0, args[0])) }),
+			complex_closure: Some(Box::new(|args| { Ok(Complex64::from(args[0].arg())) })),
+			decimal_closure: Some(Box::new(|args, prec| { Ok(if args[0].is_negative() { decimal::pi(prec) } else { BigDecimal::zero() }) })),
+			rational_closure: Some(Box::new(|args| { (!args[0].is_negative()).then(BigRational::zero) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("conj"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0]) }),
+			complex_closure: Some(Box::new(|args| { Ok(args[0].conj()) })),
+			decimal_closure: Some(Box::new(|args, _| { Ok(args[0].clone()) })),
+			rational_closure: Some(Box::new(|args| { Some(args[0].clone()) })),
+			matrix_closure: None,
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("transpose"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0]) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::transpose)),
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("det"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0]) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::det)),
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("inv"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(1.0 / args[0]) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::inv)),
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("rank"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(if args[0] == 0.0 { 0.0 } else { 1.0 }) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::rank)),
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("trace"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0]) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::trace)),
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("identity"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|_| { Err(matrix_result("identity")) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::identity)),
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("zeros"),
+			arity: Arity::Range(1, 2),
+			builtin: true,
+			closure: Box::new(|_| { Err(matrix_result("zeros")) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::zeros)),
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("solve"),
+			arity: Arity::Exact(2),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[1] / args[0]) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::solve)),
+			angle: AngleUse::None,
+		},
+		Function {
+			name: String::from("eig"),
+			arity: Arity::Exact(1),
+			builtin: true,
+			closure: Box::new(|args| { Ok(args[0]) }),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: Some(Box::new(matrix::eig)),
+			angle: AngleUse::None,
 		},
 	]
-}
\ No newline at end of file
+}
+
+/// Error for matrix functions called through `Context::try_function()`, which
+/// only returns real numbers
+fn matrix_result(name: &str) -> CalcError {
+	CalcError::new(CalcErrorType::ArgumentError, format!("\"{name}()\" returns a matrix"))
+}
@@ -1,5 +1,16 @@
 
+use std::fmt;
+use std::str::FromStr;
+use num_complex::Complex64;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use bigdecimal::{BigDecimal, One};
+
 use crate::{CalcError, CalcErrorType};
+use crate::ast::Expr;
+use num_rational::BigRational;
+
+use crate::value::{Value, RationalFormat, to_real_args, convert_angle, as_integer, decimal, fixed, list, strict, units};
 
 mod builtins;
 
@@ -12,8 +23,47 @@ pub struct Context {
 	var_table: Vec<VarTableEntry>,
 	/// Stores the value of the last successful calculation result, used when
 	/// evaluating the `ans` builtin variable;
-	pub prev_ans: Option<f64>,
-	function_table: Vec<Function>
+	pub prev_ans: Option<Value>,
+	/// Kind of numbers used during evaluation, real numbers by default
+	pub number_mode: NumberMode,
+	/// Unit of the angles taken by trigonometric functions and returned by
+	/// their inverses, radians by default
+	pub angle_mode: AngleMode,
+	/// Resolves identifiers that aren't variables as units, such as `m` or
+	/// `kHz`, enabled by default
+	pub units: bool,
+	/// Computes integer arithmetic with unbounded integers in real mode, so that
+	/// results like `30!` or `2^100` are exact, enabled by default
+	pub exact_integers: bool,
+	/// Number of significant digits kept in decimal mode, 50 by default
+	pub precision: u64,
+	/// Format used by `Value::format()` for rational results, fractions by
+	/// default
+	pub rational_format: RationalFormat,
+	/// Width and signedness of the integers used in programmer mode, signed
+	/// 64-bit integers by default
+	pub int_type: IntType,
+	/// Handling of results that don't fit in `int_type` in programmer mode,
+	/// wrapping around by default
+	pub overflow_mode: OverflowMode,
+	/// Enables implicit multiplication by juxtaposition, such as `2pi` or
+	/// `3(4+5)`, enabled by default
+	pub implicit_mul: bool,
+	/// Allows user-defined functions to shadow builtin functions, disabled by
+	/// default
+	pub allow_builtin_override: bool,
+	/// Turns division by zero, arguments outside the domain of functions and
+	/// infinite or NaN results into errors in real and complex mode, rather
+	/// than giving `inf` or `NaN`, disabled by default
+	pub strict_numerics: bool,
+	function_table: Vec<Function>,
+	user_function_table: Vec<UserFunction>,
+}
+
+impl Default for Context {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl Context {
@@ -21,33 +71,58 @@ impl Context {
 		Self {
 			var_table: builtins::get_consts(),
 			function_table: builtins::get_functions(),
+			user_function_table: Vec::new(),
 			prev_ans: None,
+			number_mode: NumberMode::Real,
+			angle_mode: AngleMode::Radians,
+			units: true,
+			exact_integers: true,
+			precision: 50,
+			rational_format: RationalFormat::Fraction,
+			int_type: IntType::I64,
+			overflow_mode: OverflowMode::Wrap,
+			implicit_mul: true,
+			allow_builtin_override: false,
+			strict_numerics: false,
 		}
 	}
 
 	/// Takes in a query string and returns an Option that is none if the variable
 	/// doesn't exist in the var table. The option contains a result that will be
 	/// Ok with the var's value if the var can be read from, otherwise an error.
-	pub fn lookup_var(&self, query: &String) -> Option<Result<f64, CalcError>> {
+	pub fn lookup_var(&self, query: &String) -> Option<Result<Value, CalcError>> {
 		// answer variable
 		if query.eq("ans") {
-			if let Some(ans) = self.prev_ans {
-				return Some(Ok(ans));
+			if let Some(ans) = &self.prev_ans {
+				return Some(Ok(ans.clone()));
 			} else {
-				return Some(Err(CalcError {
-					error_type: CalcErrorType::CalculationError,
-					msg: "Cannot use \'ans\' without a previous evaluated equation".to_string(),
-				}));
+				return Some(Err(CalcError::new(CalcErrorType::CalculationError, "Cannot use \'ans\' without a previous evaluated equation")));
+			}
+		}
+
+		// imaginary unit
+		if self.number_mode == NumberMode::Complex && query.eq("i") {
+			return Some(Ok(Value::Complex(Complex64::i())));
+		}
+
+		// constants computed to the requested precision
+		if self.number_mode == NumberMode::Decimal {
+			let prec = self.decimal_precision();
+			if query.eq("pi") {
+				return Some(Ok(Value::Decimal(decimal::pi(prec))));
+			}
+			if query.eq("e") {
+				return Some(decimal::exp(&BigDecimal::one(), prec).map(Value::Decimal));
 			}
 		}
 
 		// looking up var in table
 		for entry in &self.var_table {
 			if entry.name.eq(query) {
-				return Some(Ok(entry.value));
+				return Some(Ok(entry.value.clone()));
 			}
 		}
-		return None;
+		None
 	}
 
 	/// This function looks up a function with the specified name, returning a None
@@ -57,27 +132,343 @@ impl Context {
 	pub fn try_function(&self, name: &String, args: Vec<f64>) -> Option<Result<f64, CalcError>> {
 		for f in self.function_table.iter() {
 			if !f.name.eq(name) { continue; }
-			if f.num_args != 0 && f.num_args != args.len() {
-				return Some(Err(CalcError {
-					error_type: CalcErrorType::ArgumentError,
-					msg: "Invalid number of arguments".to_string(),
-				}));
+			if !f.arity.accepts(args.len()) {
+				return Some(Err(CalcError::new(CalcErrorType::ArgumentError, format!("Invalid number of arguments, expected {}", f.arity))));
 			}
 			return Some((f.closure)(args));
 		}
-		return None;
+		None
+	}
+
+	/// Returns true if a builtin or registered function takes angles, such as
+	/// `sin()`
+	pub(crate) fn takes_angle(&self, name: &str) -> bool {
+		self.function_table.iter().any(|f| f.name.eq(name) && f.angle == AngleUse::Argument)
+	}
+
+	/// Calls a builtin or registered function with a list of values. In complex
+	/// mode, functions with a complex implementation use it when given complex
+	/// arguments, or when the real implementation fails to produce a number, so
+	/// that `sqrt(-1)` is `i` rather than NaN
+	/// 
+	/// In decimal and rational mode, functions use their decimal or exact
+	/// implementation when they have one, falling back to the real one. The
+	/// exact implementation is also used for integer arguments in real mode
+	pub(crate) fn call_function(&self, name: &str, args: Vec<Value>) -> Option<Result<Value, CalcError>> {
+		let f = self.function_table.iter().find(|f| f.name.eq(name))?;
+		// functions taking any number of arguments take the elements of lists,
+		// others are applied to each element
+		let args = match f.arity {
+			Arity::AtLeast(_) => list::flatten(args),
+			_ => args,
+		};
+		if !f.arity.accepts(args.len()) {
+			return Some(Err(CalcError::new(CalcErrorType::ArgumentError, format!("Invalid number of arguments, expected {}", f.arity))));
+		}
+		if let Some(matrix_closure) = &f.matrix_closure {
+			return Some(matrix_closure(args));
+		}
+		if args.iter().any(|arg| matches!(arg, Value::List(_))) {
+			return Some(list::map(args, |args| self.call_scalar(f, args)));
+		}
+		Some(self.call_scalar(f, args))
+	}
+
+	/// Calls a function with arguments that aren't lists, failing on infinite
+	/// or NaN results with `strict_numerics`
+	fn call_scalar(&self, f: &Function, args: Vec<Value>) -> Result<Value, CalcError> {
+		if !self.strict_numerics {
+			return self.call_unchecked(f, args);
+		}
+		let res = self.call_unchecked(f, args.clone())?;
+		strict::check_call(&f.name, &args, &res)?;
+		Ok(res)
+	}
+
+	/// Calls a function with arguments that aren't lists, without the checks
+	/// of `strict_numerics`
+	fn call_unchecked(&self, f: &Function, args: Vec<Value>) -> Result<Value, CalcError> {
+		let name = &f.name;
+		if args.iter().any(|arg| matches!(arg, Value::Quantity(_))) {
+			if !f.builtin {
+				return Err(CalcError::new(CalcErrorType::DimensionError, format!("\"{name}()\" doesn't accept arguments with units")));
+			}
+			return units::call_function(name, args);
+		}
+		// trigonometric functions work in radians internally
+		let mode = self.angle_mode;
+		let args = match f.angle {
+			AngleUse::Argument => args.into_iter().map(|arg| convert_angle(arg, mode, AngleMode::Radians, self)).collect::<Result<_, _>>()?,
+			_ => args,
+		};
+		let res = self.call_closures(f, args);
+		match f.angle {
+			AngleUse::Argument if mode != AngleMode::Radians => res.map(snap_to_half),
+			AngleUse::Result => res.and_then(|res| convert_angle(res, AngleMode::Radians, mode, self)),
+			_ => res,
+		}
+	}
+
+	/// Calls the implementation of a function suited to the number mode
+	fn call_closures(&self, f: &Function, args: Vec<Value>) -> Result<Value, CalcError> {
+		let name = &f.name;
+		if self.number_mode == NumberMode::Programmer {
+			return self.call_programmer(f, args);
+		}
+		if let (NumberMode::Complex, Some(complex_closure)) = (self.number_mode, &f.complex_closure) {
+			let call_complex = || {
+				let args = args.iter().map(Value::to_complex).collect();
+				complex_closure(args).map(Value::from)
+			};
+			let Ok(real_args) = to_real_args(name, &args) else {
+				return call_complex();
+			};
+			let res = (f.closure)(real_args);
+			if matches!(res, Ok(n) if n.is_nan()) && !args.iter().any(Value::is_nan) {
+				return call_complex();
+			}
+			return res.map(Value::Real);
+		}
+		if let Some(rational_closure) = &f.rational_closure {
+			let exact_args = match self.number_mode {
+				NumberMode::Rational => args.iter().map(|arg| match arg {
+					Value::Rational(r) => Some(r.clone()),
+					_ => None,
+				}).collect::<Option<Vec<_>>>(),
+				// integer arguments stay exact as long as the result is an integer
+				NumberMode::Real => args.iter().map(|arg| match arg {
+					Value::Integer(_) => arg.to_rational(),
+					_ => None,
+				}).collect::<Option<Vec<_>>>(),
+				_ => None,
+			};
+			match (self.number_mode, exact_args.and_then(rational_closure)) {
+				(NumberMode::Rational, Some(res)) => return Ok(Value::Rational(res)),
+				(NumberMode::Real, Some(res)) if res.is_integer() => return Ok(Value::Integer(res.to_integer())),
+				_ => {}
+			}
+		}
+		if self.number_mode == NumberMode::Decimal {
+			let prec = self.decimal_precision();
+			if let Some(decimal_closure) = &f.decimal_closure {
+				if let Some(args) = args.iter().map(Value::to_decimal).collect::<Option<Vec<_>>>() {
+					return decimal_closure(args, prec).map(|d| Value::Decimal(decimal::round(d, prec)));
+				}
+			}
+			// functions without a decimal implementation are computed with
+			// floats, then carried on as decimals
+			let res = to_real_args(name, &args).and_then(|args| (f.closure)(args));
+			return res.map(|n| Value::Real(n).to_decimal().map_or(Value::Real(n), Value::Decimal));
+		}
+		to_real_args(name, &args).and_then(|args| (f.closure)(args)).map(Value::Real)
+	}
+
+	/// Calls a function in programmer mode, using its exact implementation when
+	/// it has one. The result has to be an integer, which is then fitted to
+	/// `int_type`
+	fn call_programmer(&self, f: &Function, args: Vec<Value>) -> Result<Value, CalcError> {
+		let name = &f.name;
+		let args = args.iter().map(fixed::to_integer).collect::<Result<Vec<_>, _>>()?;
+		let exact = f.rational_closure.as_ref().and_then(|closure| {
+			closure(args.iter().cloned().map(BigRational::from_integer).collect())
+		});
+		let res = match exact {
+			Some(res) => Value::Rational(res),
+			None => {
+				let args = args.into_iter().map(Value::Integer).collect::<Vec<_>>();
+				Value::Real((f.closure)(to_real_args(name, &args)?)?)
+			}
+		};
+		match as_integer(&res) {
+			Some(i) => fixed::fit(i, self).map(Value::Integer),
+			None => Err(CalcError::new(CalcErrorType::CalculationError, format!("\"{name}()\" gives {res}, which isn't an integer"))),
+		}
+	}
+
+	/// Precision used for decimal calculations, at least one digit
+	pub(crate) fn decimal_precision(&self) -> u64 {
+		self.precision.max(1)
+	}
+
+	/// Registers a native function that can be called from expressions, taking
+	/// the name of the function, the number of arguments it accepts, and a closure
+	/// performing the function's operation. Registering a name that's already in
+	/// use replaces the existing function, but builtin functions can only be
+	/// replaced if `allow_builtin_override` is set.
+	/// 
+	/// # Example
+	/// 
+	/// ```
+	/// # use sci_calc::{calculate, context::{Context, Arity}};
+	/// let mut ctx = Context::new();
+	/// ctx.register_function("clamp", Arity::Exact(3), |args| {
+	///     Ok(args[0].max(args[1]).min(args[2]))
+	/// }).unwrap();
+	/// assert_eq!(calculate("clamp(12, 0, 10)", &mut ctx), Ok(10.0));
+	/// ```
+	pub fn register_function<F>(&mut self, name: &str, arity: Arity, closure: F) -> Result<(), CalcError>
+	where F: Fn(Vec<f64>) -> Result<f64, CalcError> + 'static {
+		if !is_identifier(name) {
+			return Err(CalcError::new(CalcErrorType::ArgumentError, format!("Invalid function name \'{name}\'")));
+		}
+		if let Arity::Range(min, max) = arity {
+			if min > max {
+				return Err(CalcError::new(CalcErrorType::ArgumentError, format!("Invalid argument range for function \'{name}\'")));
+			}
+		}
+		let function = Function {
+			name: name.to_string(),
+			arity,
+			builtin: false,
+			closure: Box::new(closure),
+			complex_closure: None,
+			decimal_closure: None,
+			rational_closure: None,
+			matrix_closure: None,
+			angle: AngleUse::None,
+		};
+		let existing = self.function_table.iter().position(|f| f.name.eq(name));
+		if let Some(i) = existing {
+			if self.function_table[i].builtin && !self.allow_builtin_override {
+				return Err(CalcError::new(CalcErrorType::AssignmentError, format!("Can't redefine builtin function \'{name}\'")));
+			}
+		}
+		// registered functions take the place of user-defined ones
+		self.user_function_table.retain(|f| !f.name.eq(name));
+		match existing {
+			Some(i) => self.function_table[i] = function,
+			None => self.function_table.push(function),
+		}
+		Ok(())
+	}
+
+	/// Removes a registered or user-defined function, returning an error if it
+	/// doesn't exist. Builtin functions can only be removed if
+	/// `allow_builtin_override` is set.
+	pub fn remove_function(&mut self, name: &str) -> Result<(), CalcError> {
+		if let Some(i) = self.user_function_table.iter().position(|f| f.name.eq(name)) {
+			self.user_function_table.remove(i);
+			return Ok(());
+		}
+		if let Some(i) = self.function_table.iter().position(|f| f.name.eq(name)) {
+			if self.function_table[i].builtin && !self.allow_builtin_override {
+				return Err(CalcError::new(CalcErrorType::AssignmentError, format!("Can't remove builtin function \'{name}\'")));
+			}
+			self.function_table.remove(i);
+			return Ok(());
+		}
+		Err(CalcError::new(CalcErrorType::UndefinedIdentifier, format!("Unknown function \"{name}()\"")))
+	}
+
+	/// Returns true if a builtin or user-defined function with the specified
+	/// name exists
+	pub fn is_function(&self, name: &str) -> bool {
+		self.function_table.iter().any(|f| f.name.eq(name))
+			|| self.user_function_table.iter().any(|f| f.name.eq(name))
+	}
+
+	/// Looks up a user-defined function by name
+	pub(crate) fn lookup_user_function(&self, name: &str) -> Option<&UserFunction> {
+		self.user_function_table.iter().find(|f| f.name.eq(name))
+	}
+
+	/// Defines a function from a list of parameter names and an expression body,
+	/// replacing any previous user definition with the same name. Fails if the
+	/// name belongs to a builtin or registered function and
+	/// `allow_builtin_override` is unset
+	pub(crate) fn define_function(&mut self, name: &str, params: Vec<String>, body: Expr) -> Result<(), CalcError> {
+		if let Some(f) = self.function_table.iter().find(|f| f.name.eq(name)) {
+			if !self.allow_builtin_override {
+				let kind = if f.builtin { "builtin" } else { "registered" };
+				return Err(CalcError::new(CalcErrorType::AssignmentError, format!("Can't redefine {kind} function \'{name}\'")));
+			}
+		}
+		let function = UserFunction {
+			name: name.to_string(),
+			params,
+			body,
+		};
+		if let Some(entry) = self.user_function_table.iter_mut().find(|f| f.name.eq(name)) {
+			*entry = function;
+		} else {
+			self.user_function_table.push(function);
+		}
+		Ok(())
+	}
+
+	/// Defines a user variable, which can be reassigned from expressions. Fails
+	/// if the name is invalid or already belongs to a constant
+	pub fn define_var(&mut self, name: &str, val: impl Into<Value>) -> Result<(), CalcError> {
+		if !is_identifier(name) {
+			return Err(CalcError::new(CalcErrorType::ArgumentError, format!("Invalid variable name \'{name}\'")));
+		}
+		self.assign_var(&name.to_string(), val)
+	}
+
+	/// Defines a constant, which can't be reassigned from expressions. Turns an
+	/// existing variable with the same name into a constant, but fails if the
+	/// name is invalid or already belongs to a constant
+	/// 
+	/// # Example
+	/// 
+	/// ```
+	/// # use sci_calc::{calculate, context::Context};
+	/// let mut ctx = Context::new();
+	/// ctx.define_constant("g", 9.80665).unwrap();
+	/// assert_eq!(calculate("2g", &mut ctx), Ok(19.6133));
+	/// assert!(calculate("g = 10", &mut ctx).is_err());
+	/// ```
+	pub fn define_constant(&mut self, name: &str, val: impl Into<Value>) -> Result<(), CalcError> {
+		self.define_var(name, val)?;
+		if let Some(entry) = self.var_table.iter_mut().find(|entry| entry.name.eq(name)) {
+			entry.constant = true;
+		}
+		Ok(())
+	}
+
+	/// Removes a user variable, returning an error if it doesn't exist or is a
+	/// constant
+	pub fn unset_var(&mut self, name: &str) -> Result<(), CalcError> {
+		let Some(i) = self.var_table.iter().position(|entry| entry.name.eq(name)) else {
+			return Err(CalcError::new(CalcErrorType::UndefinedIdentifier, format!("Unknown variable \"{name}\"")));
+		};
+		if self.var_table[i].constant {
+			return Err(CalcError::new(CalcErrorType::AssignmentError, format!("Can't unset constant \'{name}\'")));
+		}
+		self.var_table.remove(i);
+		Ok(())
+	}
+
+	/// Returns an iterator over every variable and constant, excluding `ans`
+	pub fn vars(&self) -> impl Iterator<Item = &VarTableEntry> {
+		self.var_table.iter().filter(|entry| !entry.name.eq("ans"))
+	}
+
+	/// Returns the names of every variable that can currently be looked up,
+	/// including `ans` once there is a previous answer
+	pub(crate) fn var_names(&self) -> impl Iterator<Item = &str> {
+		let ans = self.prev_ans.as_ref().map(|_| "ans");
+		let i = (self.number_mode == NumberMode::Complex).then_some("i");
+		self.vars().map(|entry| entry.name.as_str()).chain(ans).chain(i)
+	}
+
+	/// Returns the names of every builtin, registered and user-defined function
+	pub(crate) fn function_names(&self) -> impl Iterator<Item = &str> {
+		self.function_table.iter().map(|f| f.name.as_str())
+			.chain(self.user_function_table.iter().map(|f| f.name.as_str()))
 	}
 
 	/// This function triest to assign a value to variable, returning an empty Ok
 	/// if successful, otherwise an Err
-	pub fn assign_var(&mut self, query: &String, val: f64) -> Result<(), CalcError> {
+	pub fn assign_var(&mut self, query: &String, val: impl Into<Value>) -> Result<(), CalcError> {
+		let val = val.into();
+		if self.number_mode == NumberMode::Complex && query.eq("i") {
+			return Err(CalcError::new(CalcErrorType::AssignmentError, "Can't assign value to constant \'i\'"));
+		}
 		for entry in &mut self.var_table {
 			if entry.name.eq(query) {
 				if entry.constant {
-					return Err(CalcError {
-						error_type: CalcErrorType::AssignmentError,
-						msg: format!("Can't assign value to constant \'{}\'", entry.name).to_string()
-					});
+					return Err(CalcError::new(CalcErrorType::AssignmentError, format!("Can't assign value to constant \'{}\'", entry.name)));
 				}
 				entry.value = val;
 				return Ok(());
@@ -88,22 +479,268 @@ impl Context {
 			value: val,
 			constant: false,
 		});
-		return Ok(());
-
+		Ok(())
 	}
 }
 
 /// Represents a variable, whether builtin constant or user-defined
-struct VarTableEntry {
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarTableEntry {
+	/// Name used to refer to the variable
 	pub name: String,
-	pub value: f64,
+	/// Current value of the variable
+	pub value: Value,
+	/// Whether the variable is protected from assignment
 	pub constant: bool,
 }
 
-/// Represents a builtin function, contains the name, number of args, and a closure
-/// that performs the function's operation
+/// Represents a builtin or registered native function, contains the name, number
+/// of args, and a closure that performs the function's operation
 struct Function {
 	name: String,
-	num_args: usize,
-	closure: Box<dyn Fn(Vec<f64>) -> Result<f64, CalcError>>
+	arity: Arity,
+	builtin: bool,
+	closure: Box<dyn Fn(Vec<f64>) -> Result<f64, CalcError>>,
+	/// Implementation used in complex mode, if the function supports it
+	complex_closure: Option<ComplexClosure>,
+	/// Implementation used in decimal mode, taking the number of significant
+	/// digits to compute
+	decimal_closure: Option<DecimalClosure>,
+	/// Exact implementation used in rational mode, returning None when the
+	/// result isn't rational so the real implementation is used instead
+	rational_closure: Option<RationalClosure>,
+	/// Implementation taking whole lists and matrices as arguments, used in
+	/// every number mode
+	matrix_closure: Option<MatrixClosure>,
+	/// Whether the function takes or returns an angle, which is converted from
+	/// or to the angle mode
+	angle: AngleUse,
+}
+
+/// How a function relates to angles
+#[derive(Clone, Copy, PartialEq)]
+enum AngleUse {
+	/// The function doesn't deal with angles
+	None,
+	/// The arguments of the function are angles, such as with `sin()`
+	Argument,
+	/// The result of the function is an angle, such as with `asin()`
+	Result,
+}
+
+type ComplexClosure = Box<dyn Fn(Vec<Complex64>) -> Result<Complex64, CalcError>>;
+type DecimalClosure = Box<dyn Fn(Vec<BigDecimal>, u64) -> Result<BigDecimal, CalcError>>;
+type RationalClosure = Box<dyn Fn(Vec<BigRational>) -> Option<BigRational>>;
+type MatrixClosure = Box<dyn Fn(Vec<Value>) -> Result<Value, CalcError>>;
+
+/// Kind of numbers used when evaluating expressions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberMode {
+	/// Real numbers only, operations without a real result give NaN
+	Real,
+	/// Complex numbers, with `i` as the imaginary unit
+	Complex,
+	/// Arbitrary-precision decimal numbers, computed to `precision`
+	/// significant digits
+	Decimal,
+	/// Exact fractions, functions without a rational result fall back to
+	/// real numbers
+	Rational,
+	/// Fixed-width integers of the type given by `int_type`, results that
+	/// don't fit are handled according to `overflow_mode`
+	Programmer,
+}
+impl fmt::Display for NumberMode {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "{}", match *self {
+			Self::Real => "real",
+			Self::Complex => "complex",
+			Self::Decimal => "decimal",
+			Self::Rational => "rational",
+			Self::Programmer => "programmer",
+		})
+	}
+}
+
+/// Type of the fixed-width integers used in programmer mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntType {
+	I8,
+	I16,
+	I32,
+	I64,
+	I128,
+	U8,
+	U16,
+	U32,
+	U64,
+	U128,
+}
+impl IntType {
+	/// Number of bits in an integer of this type
+	pub fn bits(&self) -> u32 {
+		match *self {
+			Self::I8 | Self::U8 => 8,
+			Self::I16 | Self::U16 => 16,
+			Self::I32 | Self::U32 => 32,
+			Self::I64 | Self::U64 => 64,
+			Self::I128 | Self::U128 => 128,
+		}
+	}
+
+	/// Returns true if integers of this type can be negative, stored in two's
+	/// complement
+	pub fn is_signed(&self) -> bool {
+		matches!(*self, Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::I128)
+	}
+
+	/// Smallest integer of this type
+	pub fn min_value(&self) -> BigInt {
+		if self.is_signed() { -(BigInt::one() << (self.bits() - 1)) } else { BigInt::from(0) }
+	}
+
+	/// Largest integer of this type
+	pub fn max_value(&self) -> BigInt {
+		let bits = if self.is_signed() { self.bits() - 1 } else { self.bits() };
+		(BigInt::one() << bits) - 1
+	}
+
+	/// Returns the bits of an integer as an unsigned number, so -1 as an `i8`
+	/// is 255
+	pub fn bit_pattern(&self, i: &BigInt) -> BigInt {
+		i.mod_floor(&(BigInt::one() << self.bits()))
+	}
+
+	/// Reads a bit pattern as an integer of this type, wrapping around when
+	/// the integer doesn't fit, so 255 as an `i8` is -1
+	pub fn wrap(&self, i: &BigInt) -> BigInt {
+		let bits = self.bit_pattern(i);
+		if self.is_signed() && bits > self.max_value() {
+			bits - (BigInt::one() << self.bits())
+		} else {
+			bits
+		}
+	}
+}
+impl fmt::Display for IntType {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		let sign = if self.is_signed() { 'i' } else { 'u' };
+		write!(formatter, "{sign}{}", self.bits())
+	}
+}
+impl FromStr for IntType {
+	type Err = CalcError;
+
+	/// Parses the name of an integer type, such as `u8` or `i64`
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"i8" => Ok(Self::I8),
+			"i16" => Ok(Self::I16),
+			"i32" => Ok(Self::I32),
+			"i64" => Ok(Self::I64),
+			"i128" => Ok(Self::I128),
+			"u8" => Ok(Self::U8),
+			"u16" => Ok(Self::U16),
+			"u32" => Ok(Self::U32),
+			"u64" => Ok(Self::U64),
+			"u128" => Ok(Self::U128),
+			_ => Err(CalcError::new(CalcErrorType::ArgumentError, format!("Unknown integer type \"{s}\""))),
+		}
+	}
+}
+
+/// Handling of integer results that don't fit in their type in programmer mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverflowMode {
+	/// Results wrap around, so 255 + 1 is 0 for unsigned 8-bit integers
+	Wrap,
+	/// Results that don't fit are a `CalculationError`
+	Error,
+}
+impl fmt::Display for OverflowMode {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "{}", match *self {
+			Self::Wrap => "wrapping",
+			Self::Error => "overflow errors",
+		})
+	}
+}
+
+/// Unit used for angles
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AngleMode {
+	/// A full turn is 2π radians
+	Radians,
+	/// A full turn is 360 degrees
+	Degrees,
+	/// A full turn is 400 gradians
+	Gradians,
+}
+impl fmt::Display for AngleMode {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "{}", match *self {
+			Self::Radians => "radians",
+			Self::Degrees => "degrees",
+			Self::Gradians => "gradians",
+		})
+	}
+}
+
+/// Number of arguments accepted by a function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity {
+	/// Exactly this many arguments
+	Exact(usize),
+	/// Any number of arguments in this inclusive range
+	Range(usize, usize),
+	/// Any number of arguments, as long as there are at least this many
+	AtLeast(usize),
+}
+impl Arity {
+	/// Returns true if a function with this arity can be called with `n` arguments
+	pub fn accepts(&self, n: usize) -> bool {
+		match *self {
+			Self::Exact(num) => n == num,
+			Self::Range(min, max) => min <= n && n <= max,
+			Self::AtLeast(min) => n >= min,
+		}
+	}
+}
+impl fmt::Display for Arity {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Self::Exact(num) => write!(formatter, "{num}"),
+			Self::Range(min, max) => write!(formatter, "{min} to {max}"),
+			Self::AtLeast(min) => write!(formatter, "at least {min}"),
+		}
+	}
+}
+
+/// Rounds a real number to the nearest multiple of 1/2 if it's within rounding
+/// error of it. Angles in degrees go through an inexact conversion to radians,
+/// this makes common results like `sin(30°)` and `cos(90°)` exact
+pub(crate) fn snap_to_half(val: Value) -> Value {
+	match val {
+		Value::Real(n) => {
+			let snapped = (n * 2.0).round() / 2.0;
+			if (n - snapped).abs() < 4.0 * f64::EPSILON { Value::Real(snapped) } else { val }
+		}
+		Value::List(list) => Value::List(list.into_iter().map(snap_to_half).collect()),
+		_ => val,
+	}
+}
+
+/// Returns true if the string is a valid variable or function name
+fn is_identifier(name: &str) -> bool {
+	let mut chars = name.chars();
+	matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+		&& chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Represents a user-defined function, contains the name, the names of its
+/// parameters, and the expression evaluated when it is called
+pub(crate) struct UserFunction {
+	pub name: String,
+	pub params: Vec<String>,
+	pub body: Expr,
 }
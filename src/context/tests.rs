@@ -1,4 +1,5 @@
 use super::*;
+use crate::ast::ExprKind;
 
 #[test]
 fn test_create_context() {
@@ -61,4 +62,154 @@ fn test_assign_var_const() {
 	let mut ctx = Context::new();
 	let res = ctx.assign_var(&String::from("pi"), 5.0);
 	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::AssignmentError));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_define_function() {
+	let mut ctx = Context::new();
+	let body = *Expr::new(ExprKind::Var(String::from("x")), 0, 1);
+	assert!(ctx.define_function("f", vec![String::from("x")], body).is_ok());
+	assert!(ctx.is_function("f"));
+	assert_eq!(ctx.lookup_user_function("f").unwrap().params.len(), 1);
+}
+
+#[test]
+fn test_define_function_builtin() {
+	let mut ctx = Context::new();
+	let res = ctx.define_function("sqrt", vec![], *Expr::new(ExprKind::Num("1".to_string()), 0, 1));
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::AssignmentError));
+}
+
+#[test]
+fn test_register_function() {
+	let mut ctx = Context::new();
+	assert!(ctx.register_function("double", Arity::Exact(1), |args| Ok(args[0] * 2.0)).is_ok());
+	let res = ctx.try_function(&String::from("double"), vec![4.0]);
+	assert_eq!(res.unwrap().unwrap(), 8.0);
+}
+
+#[test]
+fn test_register_function_arity() {
+	let mut ctx = Context::new();
+	ctx.register_function("total", Arity::Range(1, 3), |args| Ok(args.iter().sum())).unwrap();
+	let res = ctx.try_function(&String::from("total"), vec![1.0, 2.0, 3.0]);
+	assert_eq!(res.unwrap().unwrap(), 6.0);
+	let res = ctx.try_function(&String::from("total"), vec![]);
+	assert!(matches!(res.unwrap().unwrap_err().error_type, CalcErrorType::ArgumentError));
+	let res = ctx.try_function(&String::from("total"), vec![1.0, 2.0, 3.0, 4.0]);
+	assert!(matches!(res.unwrap().unwrap_err().error_type, CalcErrorType::ArgumentError));
+}
+
+#[test]
+fn test_register_function_override() {
+	let mut ctx = Context::new();
+	ctx.register_function("f", Arity::Exact(1), |args| Ok(args[0])).unwrap();
+	ctx.register_function("f", Arity::Exact(1), |args| Ok(-args[0])).unwrap();
+	let res = ctx.try_function(&String::from("f"), vec![1.0]);
+	assert_eq!(res.unwrap().unwrap(), -1.0);
+}
+
+#[test]
+fn test_register_function_builtin() {
+	let mut ctx = Context::new();
+	let res = ctx.register_function("sqrt", Arity::Exact(1), |args| Ok(args[0]));
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::AssignmentError));
+	ctx.allow_builtin_override = true;
+	assert!(ctx.register_function("sqrt", Arity::Exact(1), |args| Ok(args[0])).is_ok());
+}
+
+#[test]
+fn test_register_function_builtin_keeps_user_function() {
+	let mut ctx = Context::new();
+	ctx.allow_builtin_override = true;
+	let body = *Expr::new(ExprKind::Var(String::from("x")), 0, 1);
+	ctx.define_function("sqrt", vec![String::from("x")], body).unwrap();
+	ctx.allow_builtin_override = false;
+	let res = ctx.register_function("sqrt", Arity::Exact(1), |args| Ok(args[0]));
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::AssignmentError));
+	assert!(ctx.lookup_user_function("sqrt").is_some());
+}
+
+#[test]
+fn test_register_function_invalid_name() {
+	let mut ctx = Context::new();
+	let res = ctx.register_function("2x", Arity::Exact(1), |args| Ok(args[0]));
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::ArgumentError));
+}
+
+#[test]
+fn test_remove_function() {
+	let mut ctx = Context::new();
+	ctx.register_function("f", Arity::Exact(1), |args| Ok(args[0])).unwrap();
+	assert!(ctx.remove_function("f").is_ok());
+	assert!(!ctx.is_function("f"));
+	let res = ctx.remove_function("f");
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::UndefinedIdentifier));
+}
+
+#[test]
+fn test_remove_function_builtin() {
+	let mut ctx = Context::new();
+	let res = ctx.remove_function("sin");
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::AssignmentError));
+}
+
+#[test]
+fn test_define_constant() {
+	let mut ctx = Context::new();
+	assert!(ctx.define_constant("g", 9.80665).is_ok());
+	assert_eq!(ctx.lookup_var(&String::from("g")).unwrap().unwrap(), 9.80665);
+	let res = ctx.assign_var(&String::from("g"), 10.0);
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::AssignmentError));
+}
+
+#[test]
+fn test_define_constant_existing() {
+	let mut ctx = Context::new();
+	ctx.define_var("a", 1.0).unwrap();
+	assert!(ctx.define_constant("a", 2.0).is_ok());
+	let res = ctx.define_constant("pi", 3.0);
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::AssignmentError));
+}
+
+#[test]
+fn test_define_var_invalid_name() {
+	let mut ctx = Context::new();
+	let res = ctx.define_var("a b", 1.0);
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::ArgumentError));
+}
+
+#[test]
+fn test_unset_var() {
+	let mut ctx = Context::new();
+	ctx.define_var("a", 1.0).unwrap();
+	assert!(ctx.unset_var("a").is_ok());
+	assert!(ctx.lookup_var(&String::from("a")).is_none());
+	let res = ctx.unset_var("a");
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::UndefinedIdentifier));
+	let res = ctx.unset_var("e");
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::AssignmentError));
+}
+
+#[test]
+fn test_vars() {
+	let mut ctx = Context::new();
+	ctx.define_var("a", 1.0).unwrap();
+	let a = ctx.vars().find(|v| v.name.eq("a")).unwrap();
+	assert!(!a.constant);
+	let pi = ctx.vars().find(|v| v.name.eq("pi")).unwrap();
+	assert!(pi.constant);
+	assert!(ctx.vars().all(|v| !v.name.eq("ans")));
+}
+
+#[test]
+fn test_int_type() {
+	assert_eq!(IntType::I8.min_value(), BigInt::from(-128));
+	assert_eq!(IntType::U16.max_value(), BigInt::from(65535));
+	assert_eq!(IntType::I8.wrap(&BigInt::from(200)), BigInt::from(-56));
+	assert_eq!(IntType::U8.wrap(&BigInt::from(-1)), BigInt::from(255));
+	assert_eq!(IntType::I16.bit_pattern(&BigInt::from(-2)), BigInt::from(0xFFFE));
+	assert_eq!("u128".parse::<IntType>(), Ok(IntType::U128));
+	assert!("u7".parse::<IntType>().is_err());
+	assert_eq!(IntType::I64.to_string(), "i64");
+}
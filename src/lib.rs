@@ -15,17 +15,24 @@
 //! ```
 
 use std::fmt;
-use lalrpop_util::{lalrpop_mod, ParseError};
-use libm::tgamma;
+use lalrpop_util::lalrpop_mod;
 
 pub mod context;
 use context::*;
 
+pub mod value;
+use value::*;
+
 mod ast;
 use ast::*;
 
+mod parser;
+use parser::{mark_span, parse_statement};
+
+mod suggest;
+
 // defining lalrpop's parsing module
-lalrpop_mod!(grammar);
+lalrpop_mod!(#[allow(clippy::all)] grammar);
 
 /// Attempts to calculate a string containing a mathematical expression
 /// 
@@ -35,6 +42,14 @@ lalrpop_mod!(grammar);
 /// result containing the solution to the expression if successful, or a `CalcError`
 /// struct if not.
 /// 
+/// Statements can be separated by `;` or new lines, in which case the result of
+/// the last one is returned.
+/// 
+/// Function definitions such as `f(x) = x^2` don't produce a value, so they
+/// return NaN once the function is defined, `execute()` tells them apart from
+/// values. Results that aren't real numbers, which can come up in complex mode,
+/// are returned as a `CalculationError`.
+/// 
 /// # Example
 /// 
 /// ```
@@ -43,138 +58,432 @@ lalrpop_mod!(grammar);
 /// assert_eq!(calculate("5 + 5", &mut ctx), Ok(10.0));
 /// ```
 pub fn calculate(input_str: &str, ctx: &mut Context) -> Result<f64, CalcError> {
-	
-	let input_str = if let Some(stripped) = input_str.strip_suffix('\n') { stripped } else { input_str };
+	match execute(input_str, ctx)? {
+		Answer::Value(val) => val.to_f64().ok_or_else(|| CalcError::new(CalcErrorType::CalculationError, format!("Result {val} is not a real number"))),
+		Answer::Definition(_) => Ok(f64::NAN),
+	}
+}
 
-	// invoking grammar parser generated by lalrpop
-	let parser = grammar::targetParser::new();
-	let (tree, assignment) = match parser.parse(input_str) {
-		Ok(res) => { res }
-		Err(e) => {
-			
-			let msg = match &e {
-				ParseError::InvalidToken { location } => {
-					let pad = std::iter::repeat(" ").take(*location).collect::<String>();
-					format!("Invalid token\n| {input_str}\n| {pad}└── here")
-				},
-				ParseError::UnrecognizedEof { location: _, expected: _ } => {
-					String::from("Unexpected EOI")
-				},
-				ParseError::UnrecognizedToken { token, expected: _ } => {
-					let pad = std::iter::repeat(" ").take(token.0).collect::<String>();
-					format!("Unexpected token\n| {input_str}\n| {pad}└── here")
-				},
-				ParseError::ExtraToken { token} => {
-					let pad = std::iter::repeat(" ").take(token.0).collect::<String>();
-					format!("Extra token\n| {input_str}\n| {pad}└── here")
-				},
-				_ => String::from("Parser error"),
-			};
-			return Err(CalcError {
-				error_type: CalcErrorType::ParserError,
-				msg,
-			});
+/// Attempts to execute a string containing one or more statements
+/// 
+/// A statement is either an expression, a variable assignment such as `a = 5`
+/// or `a += 1`, or a function definition such as `f(x, y) = x^2 + y`. Statements
+/// are separated by `;` or new lines, and evaluated in order, while `#` starts a
+/// comment running to the end of the line. Works like `calculate()`, but returns
+/// an `Answer` which distinguishes function definitions from values. When there
+/// are several statements, the answer of the last one is returned, use
+/// `calculate_script()` to get all of them.
+/// 
+/// # Example
+/// 
+/// ```
+/// # use sci_calc::{execute, Answer, context::Context, value::Value};
+/// # let mut ctx = Context::new();
+/// assert_eq!(execute("f(x) = x^2", &mut ctx), Ok(Answer::Definition("f(x)".to_string())));
+/// assert_eq!(execute("f(3)", &mut ctx), Ok(Answer::Value(Value::Integer(9.into()))));
+/// assert_eq!(execute("a = 2; f(a) + 1", &mut ctx), Ok(Answer::Value(Value::Integer(5.into()))));
+/// ```
+pub fn execute(input_str: &str, ctx: &mut Context) -> Result<Answer, CalcError> {
+	let mut answer = None;
+	for statement in split_statements(input_str) {
+		answer = Some(statement.execute(ctx)?);
+	}
+	match answer {
+		Some(answer) => Ok(answer),
+		// blank input is reported as a parsing error
+		None => execute_statement(input_str, ctx),
+	}
+}
+
+/// Executes every statement of a script, separated by `;` or new lines, and
+/// returns their answers in order
+/// 
+/// Blank lines, empty statements and comments starting with `#` are skipped.
+/// Execution stops at the first statement that fails, returning a `ScriptError`
+/// with the line and column where that statement starts, along with the answers
+/// of the statements before it, which have already been applied to the
+/// `Context`.
+/// 
+/// # Example
+/// 
+/// ```
+/// # use sci_calc::{calculate_script, Answer, context::Context, value::Value};
+/// # let mut ctx = Context::new();
+/// let answers = calculate_script("a = 3\nb = a + 1; a * b", &mut ctx).unwrap();
+/// assert_eq!(answers.last(), Some(&Answer::Value(Value::Integer(12.into()))));
+/// let error = calculate_script("a = 1\nb = 2; c + 1", &mut ctx).unwrap_err();
+/// assert_eq!((error.line, error.column), (2, 8));
+/// ```
+// the error is returned once per script, so it isn't worth boxing
+#[allow(clippy::result_large_err)]
+pub fn calculate_script(input_str: &str, ctx: &mut Context) -> Result<Vec<Answer>, ScriptError> {
+	let mut answers = Vec::new();
+	for statement in split_statements(input_str) {
+		match statement.execute(ctx) {
+			Ok(answer) => answers.push(answer),
+			Err(error) => return Err(ScriptError {
+				line: statement.line,
+				column: statement.column,
+				error,
+				answers,
+			}),
 		}
-	};
-	//print!("Tree: {tree}\r\n");
+	}
+	Ok(answers)
+}
 
-	let res = evaluate_ast(*tree, ctx);
+/// Statement of a script, with the position where it starts
+struct ScriptStatement<'a> {
+	text: &'a str,
+	/// Byte offset of the statement in the script
+	offset: usize,
+	line: usize,
+	column: usize,
+}
+impl ScriptStatement<'_> {
+	/// Executes the statement, moving the span of errors from the statement to
+	/// the whole script
+	fn execute(&self, ctx: &mut Context) -> Result<Answer, CalcError> {
+		execute_statement(self.text, ctx).map_err(|error| CalcError {
+			span: error.span.map(|span| Span { start: span.start + self.offset, end: span.end + self.offset }),
+			..error
+		})
+	}
+}
 
-	if res.is_ok() {
-		let solution = res.clone().unwrap();
+/// Splits a script into its non-empty statements, separated by `;` or new lines.
+/// Comments are left out, so that a `;` inside of them doesn't start a statement
+fn split_statements(input_str: &str) -> impl Iterator<Item = ScriptStatement<'_>> {
+	input_str.lines().enumerate().flat_map(move |(line_index, line)| {
+		// `lines()` gives slices of the script, their position is where they start
+		let line_offset = line.as_ptr() as usize - input_str.as_ptr() as usize;
+		let code = line.split_once('#').map_or(line, |(code, _)| code);
+		let mut next_start = 0;
+		code.split(';').filter_map(move |text| {
+			let start = next_start;
+			next_start += text.len() + 1;
+			let indent = text.len() - text.trim_start().len();
+			(!text.trim().is_empty()).then(|| ScriptStatement {
+				text,
+				offset: line_offset + start,
+				line: line_index + 1,
+				column: line[..start + indent].chars().count() + 1,
+			})
+		})
+	})
+}
 
+/// Executes a single statement. Errors are shown along with the part of the
+/// statement they come from
+fn execute_statement(input_str: &str, ctx: &mut Context) -> Result<Answer, CalcError> {
 	
-		if assignment.is_some() {
-			// handling assignment
-			let assign_var = assignment.unwrap();
-			if let Err(e) = ctx.assign_var(&assign_var, solution) {
-				return Err(e);
-			}
-		} else {
+	let input_str = if let Some(stripped) = input_str.strip_suffix('\n') { stripped } else { input_str };
+
+	let statement = parse_statement(input_str, ctx.implicit_mul)?;
+	evaluate_statement(statement, ctx).map_err(|error| match error.span {
+		Some(span) => CalcError { msg: mark_span(&error.msg, input_str, span), ..error },
+		None => error,
+	})
+}
+
+/// Evaluates a parsed statement, updating the context
+fn evaluate_statement(statement: Statement, ctx: &mut Context) -> Result<Answer, CalcError> {
+	match statement {
+		Statement::Expr(tree) => {
+			let solution = evaluate_ast(&tree, ctx, &Scope::global())?;
 			// setting `ans` variable
-			ctx.prev_ans = Some(solution);
+			ctx.prev_ans = Some(solution.clone());
+			Ok(Answer::Value(solution))
+		}
+		Statement::Assign(name, tree) => {
+			let solution = evaluate_ast(&tree, ctx, &Scope::global())?;
+			ctx.assign_var(&name, solution.clone())?;
+			Ok(Answer::Value(solution))
+		}
+		Statement::CompoundAssign(name, span, op, tree) => {
+			let Some(current) = ctx.lookup_var(&name) else {
+				return Err(unknown_variable(&name, ctx, &Scope::global()).with_span(span));
+			};
+			let rhs = evaluate_ast(&tree, ctx, &Scope::global())?;
+			let solution = binary_op(current?, op, rhs, ctx)?;
+			ctx.assign_var(&name, solution.clone())?;
+			Ok(Answer::Value(solution))
+		}
+		Statement::FuncDef(name, params, body) => {
+			let signature = format!("{name}({})", params.join(", "));
+			ctx.define_function(&name, params, *body)?;
+			Ok(Answer::Definition(signature))
 		}
 	}
+}
+
+/// Outcome of successfully executing a statement
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+	/// Value of an evaluated expression or assignment
+	Value(Value),
+	/// Signature of a newly defined function, such as `f(x, y)`
+	Definition(String),
+}
+
+/// Maximum number of nested calls to user-defined functions, which catches
+/// functions that recurse endlessly
+const MAX_CALL_DEPTH: usize = 1000;
+
+/// Stack space that must be left before evaluating a node, and the size of the
+/// stack segments allocated when there's less, so that deeply nested
+/// expressions and recursive functions don't overflow the stack
+const STACK_RED_ZONE: usize = 64 * 1024;
+const STACK_SEGMENT_SIZE: usize = 1024 * 1024;
 
-	return res;
+/// Variables local to the body of a user-defined function, holding the arguments
+/// it was called with, and the number of calls it's nested in
+#[derive(Clone, Copy)]
+struct Scope<'a> {
+	params: &'a [String],
+	args: &'a [Value],
+	depth: usize,
+}
+impl Scope<'_> {
+	/// Scope of a top-level expression, outside of any function body
+	fn global() -> Self {
+		Self { params: &[], args: &[], depth: 0 }
+	}
+
+	fn lookup(&self, name: &str) -> Option<Value> {
+		self.params.iter().position(|p| p.eq(name)).map(|i| self.args[i].clone())
+	}
 }
 
 /// Recursive function used to evaluate the abstract syntax tree generated by
-/// the lalrpop parser
-fn evaluate_ast(root: Expr, ctx: &Context) -> Result<f64, CalcError> {
-	match root {
-		Expr::Num(n) => {
-			Ok(n)
+/// the lalrpop parser. Errors are located at the innermost node they come from
+fn evaluate_ast(root: &Expr, ctx: &Context, scope: &Scope) -> Result<Value, CalcError> {
+	stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || evaluate_node(root, ctx, scope))
+}
+
+/// Evaluates a single node of the tree, see `evaluate_ast()`
+fn evaluate_node(root: &Expr, ctx: &Context, scope: &Scope) -> Result<Value, CalcError> {
+	let res = match &root.kind {
+		ExprKind::Num(n) => {
+			parse_literal(n, ctx)
+		}
+		ExprKind::Op(left_e, op @ (Operation::And | Operation::Or), right_e) => {
+			evaluate_logical(left_e, *op, right_e, ctx, scope)
 		}
-		Expr::Op(left_e, op, right_e) => {
+		ExprKind::Op(left_e, op, right_e) => {
 			// evaluation inner expressions
-			let lhs = match evaluate_ast(*left_e, ctx) {
-				Ok(n) => n,
-				Err(e) => { return Err(e) },
-			};
-			let rhs = match evaluate_ast(*right_e, ctx) {
-				Ok(n) => n,
-				Err(e) => { return Err(e) },
-			};
+			let lhs = evaluate_ast(left_e, ctx, scope)?;
+			let rhs = evaluate_ast(right_e, ctx, scope)?;
 			// performing operation
-			let res = match op {
-				Operation::Add => { lhs + rhs }
-				Operation::Sub => { lhs - rhs }
-				Operation::Mul => { lhs * rhs }
-				Operation::Div => { lhs / rhs }
-				Operation::FloorDiv => { f64::floor(lhs / rhs) }
-				Operation::Mod => { lhs % rhs }
-				Operation::Exp => { lhs.powf(rhs) }
-			};
-			Ok(res)
-		}
-		Expr::Func(name, arg_list) => {
-			let mut args: Vec<f64> = Vec::new();
-			for arg in arg_list {
-				let val = match evaluate_ast(*arg, ctx) {
-					Ok(n) => n,
-					Err(e) => { return Err(e) },
-				};
-				args.push(val);
-			}
-			if let Some(res) = ctx.try_function(&name, args) {
-				return res;
-			}
-			return Err(CalcError {
-				error_type: CalcErrorType::UndefinedIdentifier,
-				msg: format!("Unknown function \"{name}()\""),
-			})
+			binary_op(lhs, *op, rhs, ctx)
 		}
-		Expr::Var(name) => {
-			if let Some(res) = ctx.lookup_var(&name) {
-				return res;
+		ExprKind::Func(name, arg_list) => evaluate_call(name, arg_list, ctx, scope),
+		ExprKind::Piecewise(cases, default) => evaluate_piecewise(cases, default.as_deref(), ctx, scope),
+		ExprKind::Var(name) => lookup_var(name, ctx, scope).unwrap_or_else(|| Err(unknown_variable(name, ctx, scope))).and_then(|val| {
+			if ctx.strict_numerics {
+				strict::check_var(name, &val)?;
 			}
-			return Err(CalcError {
-				error_type: CalcErrorType::UndefinedIdentifier,
-				msg: format!("Unknown variable \"{name}\""),
-			})
+			Ok(val)
+		}),
+		ExprKind::Fac(e) => evaluate_ast(e, ctx, scope).and_then(|num| factorial(num, ctx)),
+		ExprKind::Neg(e) => match &e.kind {
+			// `-128` is a signed 8-bit integer even though `128` isn't
+			ExprKind::Num(n) if ctx.number_mode == NumberMode::Programmer => fixed::parse_literal(n, true, ctx),
+			_ => evaluate_ast(e, ctx, scope).and_then(|val| negate(val, ctx)),
+		},
+		ExprKind::Not(e) => evaluate_ast(e, ctx, scope).and_then(|val| not(val, ctx)),
+		ExprKind::BitNot(e) => evaluate_ast(e, ctx, scope).and_then(|val| bit_not(val, ctx)),
+		ExprKind::List(items) => {
+			let list = items.iter().map(|item| evaluate_ast(item, ctx, scope)).collect::<Result<_, _>>();
+			list.and_then(matrix::from_list)
 		}
-		Expr::Fac(e) => {
-			let num = match evaluate_ast(*e, ctx) {
-				Ok(n) => n,
-				Err(e) => { return Err(e) },
-			};
-			return Ok(tgamma(num + 1.0));
+		ExprKind::Index(e, i) => evaluate_ast(e, ctx, scope).and_then(|list| {
+			let i = evaluate_ast(i, ctx, scope)?;
+			list::index(list, &i)
+		}),
+		ExprKind::Slice(e, start, end) => evaluate_ast(e, ctx, scope).and_then(|list| {
+			let start = start.as_ref().map(|i| evaluate_ast(i, ctx, scope)).transpose()?;
+			let end = end.as_ref().map(|i| evaluate_ast(i, ctx, scope)).transpose()?;
+			list::slice(list, start.as_ref(), end.as_ref())
+		}),
+		ExprKind::Convert(e, unit) => evaluate_ast(e, ctx, scope).and_then(|num| {
+			let target = evaluate_ast(unit, ctx, scope)?;
+			units::convert(num, target, units::format_unit_expr(unit))
+		}),
+		ExprKind::Degrees(e) => {
+			evaluate_ast(e, ctx, scope).and_then(|num| convert_angle(num, AngleMode::Degrees, ctx.angle_mode, ctx))
+		}
+	};
+	res.map_err(|error| CalcError { span: error.span.or(Some(root.span)), ..error })
+}
+
+/// Evaluates `&&` and `||`, skipping the right-hand side when the left-hand
+/// side decides the result
+fn evaluate_logical(left_e: &Expr, op: Operation, right_e: &Expr, ctx: &Context, scope: &Scope) -> Result<Value, CalcError> {
+	let lhs = evaluate_ast(left_e, ctx, scope)?;
+	// single conditions short-circuit, lists are combined element-wise
+	if !matches!(lhs, Value::List(_) | Value::Matrix(_)) {
+		let lhs_true = is_true(&lhs)?;
+		if lhs_true == (op == Operation::Or) {
+			return Ok(bool_value(lhs_true, ctx));
 		}
 	}
+	let rhs = evaluate_ast(right_e, ctx, scope)?;
+	binary_op(lhs, op, rhs, ctx)
+}
+
+/// Evaluates the value of the first condition that holds, or the default
+/// value if none of them do
+fn evaluate_piecewise(cases: &[(Expr, Expr)], default: Option<&Expr>, ctx: &Context, scope: &Scope) -> Result<Value, CalcError> {
+	for (cond, value) in cases {
+		let cond = evaluate_ast(cond, ctx, scope)?;
+		if is_true(&cond)? {
+			return evaluate_ast(value, ctx, scope);
+		}
+	}
+	match default {
+		Some(default) => evaluate_ast(default, ctx, scope),
+		None => Err(CalcError::new(CalcErrorType::CalculationError, "None of the conditions of \"piecewise()\" hold")),
+	}
+}
+
+/// Evaluates a call to a user-defined, registered or builtin function. Kept
+/// out of `evaluate_ast()` so that its stack frame stays small for deeply
+/// recursive functions
+fn evaluate_call(name: &String, arg_list: &[Expr], ctx: &Context, scope: &Scope) -> Result<Value, CalcError> {
+	let mut args: Vec<Value> = Vec::new();
+	for arg in arg_list {
+		args.push(evaluate_ast(arg, ctx, scope)?);
+	}
+	if let Some(f) = ctx.lookup_user_function(name) {
+		if f.params.len() != args.len() {
+			return Err(CalcError::new(CalcErrorType::ArgumentError, format!("Invalid number of arguments, expected {}", f.params.len())));
+		}
+		if scope.depth >= MAX_CALL_DEPTH {
+			return Err(CalcError::new(CalcErrorType::CalculationError, format!("Maximum call depth exceeded in \"{name}()\"")));
+		}
+		let inner = Scope { params: &f.params, args: &args, depth: scope.depth + 1 };
+		// errors are located at the call, spans in the body are relative to
+		// its definition
+		return evaluate_ast(&f.body, ctx, &inner).map_err(|error| CalcError { span: None, ..error });
+	}
+	// `x(3)` where `x` is a variable is an implicit multiplication
+	if ctx.implicit_mul && args.len() == 1 && !ctx.is_function(name) {
+		if let Some(res) = lookup_var(name, ctx, scope) {
+			return binary_op(res?, Operation::Mul, args.remove(0), ctx);
+		}
+	}
+	if let Some(res) = ctx.call_function(name, args) {
+		// degree literals are snapped like in degree mode, so that `sin(30°)`
+		// is 0.5 whatever the angle mode
+		if ctx.angle_mode == AngleMode::Radians && ctx.takes_angle(name) && arg_list.iter().any(is_degree_literal) {
+			return res.map(snap_to_half);
+		}
+		return res;
+	}
+	let suggestions = suggest::similar_names(name, ctx.function_names());
+	let msg = format!("Unknown function \"{name}()\"{}", suggest::did_you_mean(&suggestions, "()"));
+	Err(CalcError { suggestions, ..CalcError::new(CalcErrorType::UndefinedIdentifier, msg) })
+}
+
+/// Returns true for angles written in degrees, such as `30°`, `-45deg` or
+/// `[30°, 60°]`
+fn is_degree_literal(expr: &Expr) -> bool {
+	match &expr.kind {
+		ExprKind::Degrees(_) => true,
+		ExprKind::Neg(e) => is_degree_literal(e),
+		ExprKind::List(items) => !items.is_empty() && items.iter().all(is_degree_literal),
+		_ => false,
+	}
+}
+
+/// Error for an unknown variable, suggesting function arguments and variables
+/// with similar names
+fn unknown_variable(name: &str, ctx: &Context, scope: &Scope) -> CalcError {
+	let names = scope.params.iter().map(String::as_str).chain(ctx.var_names());
+	let suggestions = suggest::similar_names(name, names);
+	let msg = format!("Unknown variable \"{name}\"{}", suggest::did_you_mean(&suggestions, ""));
+	CalcError { suggestions, ..CalcError::new(CalcErrorType::UndefinedIdentifier, msg) }
+}
+
+/// Looks up a variable, giving function arguments priority over the context,
+/// and falling back to units
+fn lookup_var(name: &String, ctx: &Context, scope: &Scope) -> Option<Result<Value, CalcError>> {
+	if let Some(n) = scope.lookup(name) {
+		return Some(Ok(n));
+	}
+	if let Some(res) = ctx.lookup_var(name) {
+		return Some(res);
+	}
+	if ctx.units {
+		return units::lookup_unit(name).map(|q| Ok(Value::Quantity(q)));
+	}
+	None
+}
+
+/// Error returned by `calculate_script()`, locating the statement that failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptError {
+	/// Line of the failing statement, starting from 1
+	pub line: usize,
+	/// Column where the failing statement starts on its line, starting from 1
+	pub column: usize,
+	/// Error produced by the statement
+	pub error: CalcError,
+	/// Answers of the statements before the failing one
+	pub answers: Vec<Answer>,
+}
+impl fmt::Display for ScriptError {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "Line {}, column {}: {}", self.line, self.column, self.error)
+	}
+}
+
+/// Range of bytes of an input, from `start` up to but excluding `end`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+	pub start: usize,
+	pub end: usize,
 }
 
 /// Custom error handling struct
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct CalcError {
 	/// Broad type of error
 	pub error_type: CalcErrorType,
 	/// Description of error
 	pub msg: String,
+	/// Part of the input the error comes from, if it can be located
+	pub span: Option<Span>,
+	/// Tokens the parser expected instead, for parser errors. Tokens are
+	/// written as they appear in the input, such as `+` or `(`, except for
+	/// `number`, `identifier` and `function`
+	pub expected: Vec<String>,
+	/// Known names similar to an unknown variable or function, closest first
+	pub suggestions: Vec<String>,
+}
+impl CalcError {
+	/// Creates an error that isn't located in the input, such as an error
+	/// returned by a registered function
+	pub fn new(error_type: CalcErrorType, msg: impl Into<String>) -> Self {
+		Self {
+			error_type,
+			msg: msg.into(),
+			span: None,
+			expected: Vec::new(),
+			suggestions: Vec::new(),
+		}
+	}
+
+	/// Locates the error at a part of the input
+	pub fn with_span(self, span: Span) -> Self {
+		Self { span: Some(span), ..self }
+	}
 }
 impl fmt::Display for CalcError {
 	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-		write!(formatter, "{}: {}\n", self.error_type, self.msg)
+		writeln!(formatter, "{}: {}", self.error_type, self.msg)
 	}
 }
 
@@ -191,6 +500,10 @@ pub enum CalcErrorType {
 	ArgumentError,
 	/// Error generated during calculation
 	CalculationError,
+	/// Error generated when combining quantities with incompatible units
+	DimensionError,
+	/// Error generated by matrices of mismatched sizes, or singular matrices
+	MatrixError,
 }
 impl fmt::Display for CalcErrorType {
 	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -200,6 +513,8 @@ impl fmt::Display for CalcErrorType {
 			Self::AssignmentError => { "Assignment error" },
 			Self::ArgumentError => { "Argument error" },
 			Self::CalculationError => { "Calculation error" },
+			Self::DimensionError => { "Dimension error" },
+			Self::MatrixError => { "Matrix error" },
 		})
 	}
 }
@@ -0,0 +1,152 @@
+//! Parsing of statements, turning the errors of the lalrpop parser into
+//! readable messages
+//!
+//! Parser errors name the tokens that were expected instead, such as
+//! "expected a number, an identifier or '(' after '+'", and unbalanced
+//! parentheses and brackets are pointed out directly.
+
+use lalrpop_util::ParseError;
+use lalrpop_util::lexer::Token;
+
+use crate::{CalcError, CalcErrorType, Span};
+use crate::ast::{ActionError, Statement};
+use crate::grammar;
+
+/// Tokens that can start an operand
+const OPERAND_TOKENS: [&str; 5] = ["number", "function", "identifier", "(", "["];
+
+/// Prefix operators, which can come before any operand and so aren't worth
+/// listing
+const PREFIX_TOKENS: [&str; 4] = ["-", "+", "!", "~"];
+
+/// Punctuation listed by name among the expected tokens, other tokens are
+/// summed up as "an operator"
+const PUNCTUATION_TOKENS: [&str; 6] = [")", "]", ",", ":", "?", "="];
+
+/// Parses a statement, turning lalrpop's errors into a `ParserError`
+pub(crate) fn parse_statement(input_str: &str, implicit_mul: bool) -> Result<Statement, CalcError> {
+	// invoking grammar parser generated by lalrpop
+	let parser = grammar::targetParser::new();
+	parser.parse(implicit_mul, input_str).map_err(|e| parser_error(e, input_str))
+}
+
+fn parser_error(e: ParseError<usize, Token<'_>, ActionError>, input_str: &str) -> CalcError {
+	let (msg, span, expected) = match e {
+		ParseError::InvalidToken { location } => {
+			let end = input_str[location..].chars().next().map_or(location, |c| location + c.len_utf8());
+			(format!("Invalid token '{}'", &input_str[location..end]), Span { start: location, end }, Vec::new())
+		},
+		ParseError::UnrecognizedEof { location, expected } => {
+			let expected: Vec<String> = expected.iter().map(|token| token_name(token)).collect();
+			let msg = format!("Unexpected end of input{}", expectation(&expected, input_str, location));
+			(msg, Span { start: location, end: location }, expected)
+		},
+		ParseError::UnrecognizedToken { token: (start, _, end), expected } => {
+			let expected: Vec<String> = expected.iter().map(|token| token_name(token)).collect();
+			let msg = format!("Unexpected '{}'{}", &input_str[start..end], expectation(&expected, input_str, start));
+			(msg, Span { start, end }, expected)
+		},
+		ParseError::ExtraToken { token: (start, _, end) } => {
+			(format!("Extra token '{}'", &input_str[start..end]), Span { start, end }, Vec::new())
+		},
+		ParseError::User { error } => {
+			(error.msg.to_string(), Span { start: error.location, end: error.location }, Vec::new())
+		},
+	};
+	// unbalanced parentheses are usually what went wrong
+	let (msg, span) = unbalanced_bracket(input_str).unwrap_or((msg, span));
+	let error = CalcError::new(CalcErrorType::ParserError, mark_span(&msg, input_str, span)).with_span(span);
+	CalcError { expected, ..error }
+}
+
+/// Name of a token expected by the parser, the literal text of the token, or
+/// `number`, `function` or `identifier` for tokens matched by a pattern
+fn token_name(terminal: &str) -> String {
+	match terminal.strip_prefix("r#\"") {
+		Some(pattern) if pattern.starts_with("[0-9]") => "number".to_string(),
+		Some(pattern) if pattern.contains("\\(") => "function".to_string(),
+		Some(_) => "identifier".to_string(),
+		None => terminal.trim_matches('"').to_string(),
+	}
+}
+
+/// Describes the expected tokens and the token they should have followed, such
+/// as ", expected a number, an identifier or '(' after '+'"
+fn expectation(expected: &[String], input_str: &str, location: usize) -> String {
+	let has = |token: &str| expected.iter().any(|e| e == token);
+	let mut items: Vec<String> = Vec::new();
+	if has("number") {
+		items.push("a number".to_string());
+	}
+	if has("identifier") || has("function") {
+		items.push("an identifier".to_string());
+	}
+	let operator = expected.iter().map(String::as_str)
+		.any(|e| !OPERAND_TOKENS.contains(&e) && !PREFIX_TOKENS.contains(&e) && !PUNCTUATION_TOKENS.contains(&e));
+	if operator {
+		items.push("an operator".to_string());
+	}
+	items.extend(["(", "["].into_iter().chain(PUNCTUATION_TOKENS).filter(|token| has(token)).map(|token| format!("'{token}'")));
+	let Some(last) = items.pop() else {
+		return String::new();
+	};
+	let list = if items.is_empty() { last } else { format!("{} or {last}", items.join(", ")) };
+	match previous_token(&input_str[..location]) {
+		Some(token) => format!(", expected {list} after '{token}'"),
+		None => format!(", expected {list}"),
+	}
+}
+
+/// Returns the last token of an input, a run of letters and digits such as a
+/// number or name, or a run of symbols such as an operator
+fn previous_token(input_str: &str) -> Option<&str> {
+	let input_str = input_str.trim_end();
+	let last = input_str.chars().next_back()?;
+	let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+	let start = if is_word(last) {
+		input_str.rfind(|c: char| !is_word(c)).map_or(0, |i| i + input_str[i..].chars().next().map_or(1, char::len_utf8))
+	} else {
+		// brackets are always tokens of their own
+		let is_symbol = |c: char| !is_word(c) && !c.is_whitespace() && !"()[]".contains(c);
+		match last {
+			'(' | ')' | '[' | ']' => input_str.len() - 1,
+			_ => input_str.rfind(|c: char| !is_symbol(c)).map_or(0, |i| i + input_str[i..].chars().next().map_or(1, char::len_utf8)),
+		}
+	};
+	Some(&input_str[start..])
+}
+
+/// Finds the first closing parenthesis or bracket that doesn't match an opening
+/// one, or else the last one left open. Comments are skipped
+fn unbalanced_bracket(input_str: &str) -> Option<(String, Span)> {
+	let mut open: Vec<(usize, char)> = Vec::new();
+	let mut in_comment = false;
+	for (i, c) in input_str.char_indices() {
+		match c {
+			'\n' => in_comment = false,
+			_ if in_comment => {}
+			'#' => in_comment = true,
+			'(' | '[' => open.push((i, c)),
+			')' | ']' => {
+				let opening = if c == ')' { '(' } else { '[' };
+				match open.pop() {
+					Some((_, o)) if o == opening => {}
+					_ => return Some((format!("Unmatched '{c}'"), Span { start: i, end: i + 1 })),
+				}
+			}
+			_ => {}
+		}
+	}
+	open.pop().map(|(i, c)| (format!("Unclosed '{c}'"), Span { start: i, end: i + 1 }))
+}
+
+/// Appends the input to an error message, underlining the part of it the error
+/// comes from
+pub(crate) fn mark_span(msg: &str, input_str: &str, span: Span) -> String {
+	let pad = " ".repeat(input_str.get(..span.start).unwrap_or(input_str).chars().count());
+	let marker = match input_str.get(span.start..span.end).map_or(0, |s| s.chars().count()) {
+		0 | 1 => "└── here".to_string(),
+		width => format!("└{}┘ here", "─".repeat(width - 2)),
+	};
+	format!("{msg}\n| {input_str}\n| {pad}{marker}")
+}
@@ -0,0 +1,54 @@
+//! "Did you mean" suggestions for unknown variables and functions
+//!
+//! Unknown names are compared to the known ones by edit distance, ignoring
+//! case, so that `sqr` suggests `sqrt` and `Pi` suggests `pi`. Short names
+//! only match names that differ from them in case, as every one or two
+//! letter name is within a single edit of many others.
+
+/// Largest number of suggestions given for an unknown name
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the names within a few edits of an unknown name, closest first
+pub(crate) fn similar_names<'a>(name: &str, names: impl Iterator<Item = &'a str>) -> Vec<String> {
+	let query = name.to_lowercase();
+	let max_distance = query.chars().count() / 3;
+	let mut matches: Vec<(usize, &str)> = names
+		.filter(|candidate| !candidate.eq(&name))
+		.map(|candidate| (edit_distance(&query, &candidate.to_lowercase()), candidate))
+		.filter(|(distance, _)| *distance <= max_distance)
+		.collect();
+	matches.sort();
+	matches.dedup_by(|a, b| a.1 == b.1);
+	matches.into_iter().take(MAX_SUGGESTIONS).map(|(_, candidate)| candidate.to_string()).collect()
+}
+
+/// Formats suggestions to follow an error message, such as
+/// `, did you mean "sin()" or "sinh()"?`, or nothing without suggestions
+pub(crate) fn did_you_mean(suggestions: &[String], suffix: &str) -> String {
+	let quoted: Vec<String> = suggestions.iter().map(|name| format!("\"{name}{suffix}\"")).collect();
+	match quoted.split_last() {
+		None => String::new(),
+		Some((last, [])) => format!(", did you mean {last}?"),
+		Some((last, rest)) => format!(", did you mean {} or {last}?", rest.join(", ")),
+	}
+}
+
+/// Edit distance between two strings, counting insertions, deletions and
+/// substitutions of characters, and swaps of adjacent characters
+fn edit_distance(a: &str, b: &str) -> usize {
+	let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+	// rows of distances between prefixes of `a` and every prefix of `b`
+	let mut before: Vec<usize> = Vec::new();
+	let mut previous: Vec<usize> = (0..=b.len()).collect();
+	for i in 1..=a.len() {
+		let mut row = vec![i; b.len() + 1];
+		for j in 1..=b.len() {
+			row[j] = (previous[j - 1] + usize::from(a[i - 1] != b[j - 1])).min(previous[j] + 1).min(row[j - 1] + 1);
+			if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+				row[j] = row[j].min(before[j - 2] + 1);
+			}
+		}
+		before = std::mem::replace(&mut previous, row);
+	}
+	previous[b.len()]
+}
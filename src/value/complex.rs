@@ -0,0 +1,77 @@
+use std::f64::consts::PI;
+use num_complex::Complex64;
+
+use crate::ast::Operation;
+
+/// Coefficients for the Lanczos approximation of the gamma function, with g = 7
+const LANCZOS_G: f64 = 7.0;
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+	0.999_999_999_999_809_9,
+	676.520_368_121_885_1,
+	-1_259.139_216_722_402_8,
+	771.323_428_777_653_1,
+	-176.615_029_162_140_6,
+	12.507_343_278_686_905,
+	-0.138_571_095_265_720_12,
+	9.984_369_578_019_572e-6,
+	1.505_632_735_149_311_6e-7,
+];
+
+/// Performs a binary operation on two complex numbers
+pub(crate) fn complex_op(lhs: Complex64, op: Operation, rhs: Complex64) -> Complex64 {
+	match op {
+		Operation::Add => { lhs + rhs }
+		Operation::Sub => { lhs - rhs }
+		Operation::Mul => { lhs * rhs }
+		Operation::Div => { lhs / rhs }
+		Operation::FloorDiv => { complex_floor(lhs / rhs) }
+		Operation::Mod => { lhs - rhs * complex_floor(lhs / rhs) }
+		Operation::Exp => { complex_pow(lhs, rhs) }
+		_ => unreachable!("Logical and bitwise operations are evaluated separately"),
+	}
+}
+
+/// Raises a complex number to a complex power, using repeated multiplication
+/// for integer exponents to avoid rounding errors such as `i^2 = -1+1e-16i`
+pub(crate) fn complex_pow(base: Complex64, exp: Complex64) -> Complex64 {
+	if exp.im == 0.0 && exp.re.fract() == 0.0 && exp.re.abs() <= i32::MAX as f64 {
+		return base.powi(exp.re as i32);
+	}
+	if base == Complex64::new(0.0, 0.0) && exp.re > 0.0 {
+		return base;
+	}
+	base.powc(exp)
+}
+
+/// Rounds both parts of a complex number down
+pub(crate) fn complex_floor(z: Complex64) -> Complex64 {
+	Complex64::new(z.re.floor(), z.im.floor())
+}
+
+/// Gamma function over the complex plane, using the Lanczos approximation and
+/// the reflection formula for the left half-plane
+pub(crate) fn complex_gamma(z: Complex64) -> Complex64 {
+	if z.re < 0.5 {
+		return PI / ((PI * z).sin() * complex_gamma(1.0 - z));
+	}
+	let z = z - 1.0;
+	let mut x = Complex64::new(LANCZOS_COEFFICIENTS[0], 0.0);
+	for (i, c) in LANCZOS_COEFFICIENTS.iter().enumerate().skip(1) {
+		x += c / (z + i as f64);
+	}
+	let t = z + LANCZOS_G + 0.5;
+	(2.0 * PI).sqrt() * t.powc(z + 0.5) * (-t).exp() * x
+}
+
+/// Formats a complex number like `3+4i`, leaving out a zero real part and a
+/// unit imaginary coefficient
+pub(crate) fn format_complex(z: Complex64) -> String {
+	let im = if z.im.abs() == 1.0 { String::new() } else { z.im.abs().to_string() };
+	if z.re == 0.0 {
+		let sign = if z.im < 0.0 { "-" } else { "" };
+		format!("{sign}{im}i")
+	} else {
+		let sign = if z.im < 0.0 { '-' } else { '+' };
+		format!("{}{sign}{im}i", z.re)
+	}
+}
@@ -0,0 +1,502 @@
+//! Arbitrary-precision decimal arithmetic used in decimal mode
+//!
+//! Results are rounded to a number of significant digits. Transcendental
+//! functions reduce their argument to a small range and sum a Taylor series,
+//! carrying a few guard digits through intermediate results.
+
+use bigdecimal::{BigDecimal, Context as DecimalContext, One, Signed, ToPrimitive, Zero};
+use bigdecimal::num_bigint::BigInt;
+use num_integer::Integer;
+
+use crate::{CalcError, CalcErrorType};
+use crate::ast::Operation;
+
+/// Extra digits carried through intermediate results to absorb rounding errors
+pub(crate) const GUARD_DIGITS: u64 = 10;
+
+/// Largest integer for which factorials are calculated by direct multiplication
+const MAX_EXACT_FACTORIAL: u64 = 10_000;
+
+/// Order of magnitude of the smallest angles trig functions reject. Reducing
+/// an angle takes pi to as many more digits, which gets slow past this
+const MAX_ANGLE_MAGNITUDE: i64 = 500;
+
+/// Rounds a number to a number of significant digits
+pub(crate) fn round(x: BigDecimal, prec: u64) -> BigDecimal {
+	if x.digits() > prec { x.with_prec(prec) } else { x }
+}
+
+/// Returns 10^-prec, the size of the last digit at a given precision
+fn epsilon(prec: u64) -> BigDecimal {
+	BigDecimal::new(1.into(), prec as i64)
+}
+
+fn decimal_context(prec: u64) -> DecimalContext {
+	DecimalContext::default().with_prec(prec).expect("Precision must be non-zero")
+}
+
+/// Error for an argument outside of a function's domain
+pub(crate) fn domain_error(name: &str, x: &BigDecimal) -> CalcError {
+	CalcError::new(CalcErrorType::CalculationError, format!("Argument {} is outside the domain of \"{name}()\"", x.normalized()))
+}
+
+pub(crate) fn division_by_zero() -> CalcError {
+	CalcError::new(CalcErrorType::CalculationError, "Division by zero")
+}
+
+fn overflow() -> CalcError {
+	CalcError::new(CalcErrorType::CalculationError, "Result is too large to represent")
+}
+
+/// Performs a binary operation on two decimal numbers
+pub(crate) fn decimal_op(lhs: &BigDecimal, op: Operation, rhs: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	match op {
+		Operation::Add => Ok(round(lhs + rhs, prec)),
+		Operation::Sub => Ok(round(lhs - rhs, prec)),
+		Operation::Mul => Ok(round(lhs * rhs, prec)),
+		Operation::Div => div(lhs, rhs, prec),
+		Operation::FloorDiv => {
+			if rhs.is_zero() {
+				return Err(division_by_zero());
+			}
+			let (lhs, rhs, _) = align_digits(lhs, rhs);
+			Ok(round(BigDecimal::from(lhs.div_floor(&rhs)), prec))
+		}
+		Operation::Mod => {
+			if rhs.is_zero() {
+				return Err(division_by_zero());
+			}
+			// truncated remainder, taking the sign of the dividend like `f64`
+			let (lhs, rhs, scale) = align_digits(lhs, rhs);
+			Ok(round(BigDecimal::new(lhs % rhs, scale), prec))
+		}
+		Operation::Exp => pow(lhs, rhs, prec),
+		_ => unreachable!("Logical and bitwise operations are evaluated separately"),
+	}
+}
+
+/// Divides two numbers to a number of significant digits
+pub(crate) fn div(lhs: &BigDecimal, rhs: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if rhs.is_zero() {
+		return Err(division_by_zero());
+	}
+	let inverse = rhs.inverse_with_context(&decimal_context(prec + GUARD_DIGITS));
+	Ok(round(lhs * inverse, prec))
+}
+
+/// Returns the digits of two numbers scaled to the same number of decimal
+/// places, along with that number of places
+fn align_digits(lhs: &BigDecimal, rhs: &BigDecimal) -> (BigInt, BigInt, i64) {
+	let scale = lhs.fractional_digit_count().max(rhs.fractional_digit_count());
+	let (lhs, _) = lhs.with_scale(scale).into_bigint_and_exponent();
+	let (rhs, _) = rhs.with_scale(scale).into_bigint_and_exponent();
+	(lhs, rhs, scale)
+}
+
+/// Raises a number to a power, exactly for integer exponents and through
+/// `exp(y * ln(x))` otherwise
+pub(crate) fn pow(x: &BigDecimal, y: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if y.is_integer() {
+		if let Some(n) = y.to_i64() {
+			if x.is_zero() && n < 0 {
+				return Err(division_by_zero());
+			}
+			let res = x.powi_with_context(n, &decimal_context(prec + GUARD_DIGITS));
+			return Ok(round(res, prec));
+		}
+	}
+	if x.is_zero() {
+		return if y.is_positive() { Ok(BigDecimal::zero()) } else { Err(division_by_zero()) };
+	}
+	if x.is_negative() {
+		return Err(CalcError::new(CalcErrorType::CalculationError, "Can't raise a negative number to a fractional power"));
+	}
+	let wp = prec + GUARD_DIGITS;
+	let exponent = round(y * ln(x, wp)?, wp);
+	Ok(round(exp(&exponent, wp)?, prec))
+}
+
+/// Square root
+pub(crate) fn sqrt(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	match x.sqrt_with_context(&decimal_context(prec)) {
+		Some(res) => Ok(res),
+		None => Err(domain_error("sqrt", x)),
+	}
+}
+
+/// The `n`th root of a number, negative numbers only have odd integer roots
+pub(crate) fn root(x: &BigDecimal, n: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if n.is_zero() {
+		return Err(division_by_zero());
+	}
+	let wp = prec + GUARD_DIGITS;
+	let inverse = div(&BigDecimal::one(), n, wp)?;
+	if x.is_negative() {
+		let odd = n.is_integer() && n.to_i64().is_some_and(|n| n % 2 != 0);
+		if !odd {
+			return Err(domain_error("root", x));
+		}
+		return Ok(-round(pow(&-x, &inverse, wp)?, prec));
+	}
+	Ok(round(pow(x, &inverse, wp)?, prec))
+}
+
+/// Pi, calculated with Machin's formula `pi = 16 atan(1/5) - 4 atan(1/239)`
+pub(crate) fn pi(prec: u64) -> BigDecimal {
+	let wp = prec + GUARD_DIGITS;
+	let a = atan_series(&div_int(&BigDecimal::one(), 5, wp), wp);
+	let b = atan_series(&div_int(&BigDecimal::one(), 239, wp), wp);
+	round(a * 16 - b * 4, prec)
+}
+
+/// Divides a number by a small integer
+fn div_int(x: &BigDecimal, n: i64, prec: u64) -> BigDecimal {
+	div(x, &BigDecimal::from(n), prec).expect("Divisor must be non-zero")
+}
+
+/// Exponential function
+pub(crate) fn exp(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if x.is_zero() {
+		return Ok(BigDecimal::one());
+	}
+	if x.abs() > 1_000_000_000_000_000i64 {
+		return if x.is_negative() { Ok(BigDecimal::zero()) } else { Err(overflow()) };
+	}
+	// halving the argument until the series converges quickly, then squaring
+	// the result back up
+	let threshold = BigDecimal::new(1.into(), 2);
+	let mut r = x.clone();
+	let mut halvings = 0;
+	while r.abs() > threshold {
+		r = r.half();
+		halvings += 1;
+	}
+	let wp = prec + GUARD_DIGITS + halvings / 3;
+	let eps = epsilon(wp);
+	let mut sum = BigDecimal::one();
+	let mut term = BigDecimal::one();
+	let mut n = 1;
+	while term.abs() > eps {
+		term = div_int(&round(term * &r, wp), n, wp);
+		sum += &term;
+		n += 1;
+	}
+	for _ in 0..halvings {
+		sum = round(sum.square(), wp);
+	}
+	Ok(round(sum, prec))
+}
+
+/// Natural logarithm
+pub(crate) fn ln(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if !x.is_positive() {
+		return Err(domain_error("ln", x));
+	}
+	let wp = prec + GUARD_DIGITS;
+	// splitting x into y * 10^k with y in [1, 10), numbers just below 1 are
+	// left as they are to avoid cancellation between the two terms
+	let k = match x.order_of_magnitude() {
+		-1 => 0,
+		k => k,
+	};
+	let (digits, scale) = x.as_bigint_and_exponent();
+	let y = BigDecimal::new(digits, scale + k);
+	let mut res = ln_reduced(&y, wp);
+	if k != 0 {
+		res += ln_reduced(&BigDecimal::from(10), wp) * k;
+	}
+	Ok(round(res, prec))
+}
+
+/// Logarithm of x with an arbitrary base
+pub(crate) fn log(x: &BigDecimal, base: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	let wp = prec + GUARD_DIGITS;
+	if !x.is_positive() {
+		return Err(domain_error("log", x));
+	}
+	if !base.is_positive() || base.is_one() {
+		return Err(domain_error("log", base));
+	}
+	let res = div(&ln(x, wp)?, &ln(base, wp)?, wp)?;
+	Ok(round(res, prec))
+}
+
+/// Natural logarithm of a number in [1, 10), taking square roots until it's
+/// close to 1 and then using the series `ln(y) = 2 atanh((y-1)/(y+1))`
+fn ln_reduced(y: &BigDecimal, prec: u64) -> BigDecimal {
+	let threshold = BigDecimal::new(1.into(), 2);
+	let mut y = y.clone();
+	let mut roots = 0;
+	let wp = prec + 3;
+	while (&y - BigDecimal::one()).abs() > threshold {
+		y = y.sqrt_with_context(&decimal_context(wp)).expect("Argument must be positive");
+		roots += 1;
+	}
+	let u = div(&(&y - BigDecimal::one()), &(&y + BigDecimal::one()), wp).expect("Divisor must be positive");
+	let u_squared = round(u.square(), wp);
+	let eps = epsilon(wp);
+	let mut power = u.clone();
+	let mut sum = u;
+	let mut n = 3;
+	loop {
+		power = round(power * &u_squared, wp);
+		let term = div_int(&power, n, wp);
+		if term.abs() < eps { break; }
+		sum += term;
+		n += 2;
+	}
+	round(sum * BigDecimal::from(2u64 << roots), prec)
+}
+
+/// Taylor series of the arctangent, converges quickly for small arguments
+fn atan_series(x: &BigDecimal, prec: u64) -> BigDecimal {
+	let x_squared = round(x.square(), prec);
+	let eps = epsilon(prec);
+	let mut power = x.clone();
+	let mut sum = x.clone();
+	let mut n = 3;
+	loop {
+		power = -round(power * &x_squared, prec);
+		let term = div_int(&power, n, prec);
+		if term.abs() < eps { break; }
+		sum += term;
+		n += 2;
+	}
+	sum
+}
+
+/// Arctangent, reducing arguments above 1 with `atan(x) = pi/2 - atan(1/x)` and
+/// halving the rest with `atan(x) = 2 atan(x / (1 + sqrt(1 + x^2)))`
+pub(crate) fn atan(x: &BigDecimal, prec: u64) -> BigDecimal {
+	if x.is_zero() {
+		return BigDecimal::zero();
+	}
+	let wp = prec + GUARD_DIGITS;
+	let mut a = x.abs();
+	let inverted = a > BigDecimal::one();
+	if inverted {
+		a = div(&BigDecimal::one(), &a, wp).expect("Divisor must be non-zero");
+	}
+	let threshold = BigDecimal::new(1.into(), 1);
+	let mut halvings = 0;
+	while a > threshold {
+		let root = (BigDecimal::one() + a.square()).sqrt_with_context(&decimal_context(wp)).expect("Argument must be positive");
+		a = div(&a, &(root + BigDecimal::one()), wp).expect("Divisor must be positive");
+		halvings += 1;
+	}
+	let mut res = atan_series(&a, wp) * BigDecimal::from(1u64 << halvings);
+	if inverted {
+		res = pi(wp).half() - res;
+	}
+	if x.is_negative() {
+		res = -res;
+	}
+	round(res, prec)
+}
+
+/// Arcsine, defined on [-1, 1]
+pub(crate) fn asin(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	let wp = prec + GUARD_DIGITS;
+	match x.abs().cmp(&BigDecimal::one()) {
+		std::cmp::Ordering::Greater => Err(domain_error("asin", x)),
+		std::cmp::Ordering::Equal => {
+			let res = round(pi(prec).half(), prec);
+			Ok(if x.is_negative() { -res } else { res })
+		}
+		std::cmp::Ordering::Less => {
+			let cos = sqrt(&(BigDecimal::one() - x.square()), wp)?;
+			Ok(atan(&div(x, &cos, wp)?, prec))
+		}
+	}
+}
+
+/// Arccosine, defined on [-1, 1]
+pub(crate) fn acos(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if x.abs() > BigDecimal::one() {
+		return Err(domain_error("acos", x));
+	}
+	let wp = prec + GUARD_DIGITS;
+	Ok(round(pi(wp).half() - asin(x, wp)?, prec))
+}
+
+/// Reduces an angle to [-pi, pi], failing for angles too large to reduce in
+/// a reasonable time
+fn reduce_angle(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	let magnitude = x.order_of_magnitude();
+	if magnitude >= MAX_ANGLE_MAGNITUDE {
+		return Err(CalcError::new(CalcErrorType::CalculationError, format!("Angle {} is too large, trig functions take angles below 1e{MAX_ANGLE_MAGNITUDE}", x.normalized())));
+	}
+	let wp = prec + magnitude.max(0) as u64;
+	let two_pi = pi(wp).double();
+	let turns = div(x, &two_pi, wp)?.round(0);
+	Ok(round(x - two_pi * turns, prec))
+}
+
+/// Sums the series `x^k/k! - x^(k+2)/(k+2)! + ...` shared by sine and cosine
+fn trig_series(x: &BigDecimal, first: BigDecimal, k: i64, prec: u64) -> BigDecimal {
+	let x_squared = round(x.square(), prec);
+	let eps = epsilon(prec);
+	let mut term = first;
+	let mut sum = term.clone();
+	let mut n = k;
+	loop {
+		term = -div_int(&round(term * &x_squared, prec), (n + 1) * (n + 2), prec);
+		if term.abs() < eps { break; }
+		sum += &term;
+		n += 2;
+	}
+	sum
+}
+
+/// Sine
+pub(crate) fn sin(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	let wp = prec + GUARD_DIGITS;
+	let r = reduce_angle(x, wp)?;
+	Ok(round(trig_series(&r, r.clone(), 1, wp), prec))
+}
+
+/// Cosine
+pub(crate) fn cos(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	let wp = prec + GUARD_DIGITS;
+	let r = reduce_angle(x, wp)?;
+	Ok(round(trig_series(&r, BigDecimal::one(), 0, wp), prec))
+}
+
+/// Tangent
+pub(crate) fn tan(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	let wp = prec + GUARD_DIGITS;
+	div(&sin(x, wp)?, &cos(x, wp)?, prec)
+}
+
+/// Working precision that makes up for the cancellation in `e^x - e^-x` and
+/// `ln(1 + x)` when `x` is small
+fn small_arg_precision(x: &BigDecimal, prec: u64) -> u64 {
+	prec + GUARD_DIGITS + (-x.order_of_magnitude()).max(0) as u64
+}
+
+/// Hyperbolic sine
+pub(crate) fn sinh(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if x.is_zero() {
+		return Ok(BigDecimal::zero());
+	}
+	let wp = small_arg_precision(x, prec);
+	let e = exp(x, wp)?;
+	let inverse = div(&BigDecimal::one(), &e, wp)?;
+	Ok(round((e - inverse).half(), prec))
+}
+
+/// Hyperbolic cosine
+pub(crate) fn cosh(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	let wp = prec + GUARD_DIGITS;
+	let e = exp(x, wp)?;
+	let inverse = div(&BigDecimal::one(), &e, wp)?;
+	Ok(round((e + inverse).half(), prec))
+}
+
+/// Hyperbolic tangent
+pub(crate) fn tanh(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	// tanh(x) = (e^2x - 1) / (e^2x + 1), saturating for large arguments
+	if x.abs() > (prec as i64 + 10) {
+		return Ok(if x.is_negative() { -BigDecimal::one() } else { BigDecimal::one() });
+	}
+	let wp = small_arg_precision(x, prec);
+	let e = exp(&x.double(), wp)?;
+	div(&(&e - BigDecimal::one()), &(&e + BigDecimal::one()), prec)
+}
+
+/// Inverse hyperbolic sine, `ln(x + sqrt(x^2 + 1))`
+pub(crate) fn asinh(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if x.is_zero() {
+		return Ok(BigDecimal::zero());
+	}
+	let wp = small_arg_precision(x, prec);
+	let a = x.abs();
+	let res = ln(&(sqrt(&(a.square() + BigDecimal::one()), wp)? + &a), wp)?;
+	Ok(round(if x.is_negative() { -res } else { res }, prec))
+}
+
+/// Inverse hyperbolic cosine, `ln(x + sqrt(x^2 - 1))`, defined for x >= 1
+pub(crate) fn acosh(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if x < &BigDecimal::one() {
+		return Err(domain_error("acosh", x));
+	}
+	let wp = prec + GUARD_DIGITS;
+	let res = ln(&(sqrt(&(x.square() - BigDecimal::one()), wp)? + x), wp)?;
+	Ok(round(res, prec))
+}
+
+/// Inverse hyperbolic tangent, `ln((1 + x) / (1 - x)) / 2`, defined on (-1, 1)
+pub(crate) fn atanh(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if x.abs() >= BigDecimal::one() {
+		return Err(domain_error("atanh", x));
+	}
+	if x.is_zero() {
+		return Ok(BigDecimal::zero());
+	}
+	let wp = small_arg_precision(x, prec);
+	let ratio = div(&(BigDecimal::one() + x), &(BigDecimal::one() - x), wp)?;
+	Ok(round(ln(&ratio, wp)?.half(), prec))
+}
+
+/// Factorial, exact for integers and extended to other numbers with the
+/// gamma function
+pub(crate) fn factorial(x: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if x.is_integer() {
+		if x.is_negative() {
+			return Err(domain_error("fac", x));
+		}
+		if let Some(n) = x.to_u64().filter(|n| *n <= MAX_EXACT_FACTORIAL) {
+			let wp = prec + GUARD_DIGITS;
+			let mut res = BigDecimal::one();
+			for i in 2..=n {
+				res = round(res * BigDecimal::from(i), wp);
+			}
+			return Ok(round(res, prec));
+		}
+	}
+	gamma(&(x + BigDecimal::one()), prec)
+}
+
+/// Gamma function, using the reflection formula for arguments below 1/2 and
+/// Spouge's approximation for the rest
+pub(crate) fn gamma(z: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	if !z.is_positive() && z.is_integer() {
+		return Err(domain_error("gamma", z));
+	}
+	let wp = prec + GUARD_DIGITS;
+	let half = BigDecimal::new(5.into(), 1);
+	if z < &half {
+		// gamma(z) = pi / (sin(pi z) gamma(1 - z))
+		let pi = pi(wp);
+		let sin = sin(&round(&pi * z, wp), wp)?;
+		let denominator = round(sin * gamma(&(BigDecimal::one() - z), wp)?, wp);
+		return div(&pi, &denominator, prec);
+	}
+	Ok(round(spouge(&(z - BigDecimal::one()), wp)?, prec))
+}
+
+/// Spouge's approximation of `gamma(w + 1)`. The coefficients alternate in sign
+/// and cancel out, so they're calculated at twice the precision
+fn spouge(w: &BigDecimal, prec: u64) -> Result<BigDecimal, CalcError> {
+	// the relative error is below (2 pi)^-a, so each digit needs about 1.26 terms
+	let a = (prec * 126 / 100 + 2) as i64;
+	let wp = prec * 2 + GUARD_DIGITS;
+	let half = BigDecimal::new(5.into(), 1);
+	let mut sum = sqrt(&pi(wp).double(), wp)?;
+	let mut k_fac = BigDecimal::one();
+	for k in 1..a {
+		if k > 1 {
+			k_fac = round(k_fac * BigDecimal::from(k - 1), wp);
+		}
+		let base = BigDecimal::from(a - k);
+		let exponent = round((BigDecimal::from(k) - &half) * ln(&base, wp)? + &base, wp);
+		let mut c = div(&exp(&exponent, wp)?, &k_fac, wp)?;
+		if k % 2 == 0 {
+			c = -c;
+		}
+		sum += div(&c, &(w + BigDecimal::from(k)), wp)?;
+	}
+	let shifted = w + BigDecimal::from(a);
+	let exponent = round((w + &half) * ln(&shifted, wp)? - &shifted, wp);
+	Ok(round(exp(&exponent, wp)? * sum, prec))
+}
@@ -0,0 +1,119 @@
+//! Fixed-width integer arithmetic used in programmer mode
+//!
+//! In programmer mode every value is an integer of the type given by
+//! `Context::int_type`, such as an unsigned 8-bit integer. Results that don't
+//! fit in the type either wrap around or are reported as overflows, depending
+//! on `Context::overflow_mode`. Division truncates towards zero, and bitwise
+//! operations always wrap around, so `~0` is 255 for unsigned 8-bit integers.
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{CalcError, CalcErrorType};
+use crate::ast::Operation;
+use crate::context::{Context, OverflowMode};
+use super::{Value, as_integer, integer, parse_radix_literal, rational};
+
+fn overflow_error(res: impl std::fmt::Display, ctx: &Context) -> CalcError {
+	CalcError::new(CalcErrorType::CalculationError, format!("Integer overflow, {res} doesn't fit in {}", ctx.int_type))
+}
+
+fn integer_error(val: impl std::fmt::Display) -> CalcError {
+	CalcError::new(CalcErrorType::CalculationError, format!("Programmer mode only works with integers, not {val}"))
+}
+
+/// Returns a value as an integer, failing for anything else as programmer mode
+/// only works with integers
+pub(crate) fn to_integer(val: &Value) -> Result<BigInt, CalcError> {
+	as_integer(val).ok_or_else(|| integer_error(val))
+}
+
+/// Fits an integer to the integer type of the context, wrapping it around or
+/// failing with an overflow error if it doesn't fit
+pub(crate) fn fit(i: BigInt, ctx: &Context) -> Result<BigInt, CalcError> {
+	let ty = ctx.int_type;
+	match ctx.overflow_mode {
+		OverflowMode::Wrap => Ok(ty.wrap(&i)),
+		OverflowMode::Error if i < ty.min_value() || i > ty.max_value() => Err(overflow_error(i, ctx)),
+		OverflowMode::Error => Ok(i),
+	}
+}
+
+/// Parses a number literal as an integer, negated if it follows a minus sign
+/// so that `-128` is a valid signed 8-bit integer even though `128` isn't.
+/// Hexadecimal, binary and octal literals are bit patterns, so `0xFF` is -1
+/// for signed 8-bit integers
+pub(crate) fn parse_literal(literal: &str, negative: bool, ctx: &Context) -> Result<Value, CalcError> {
+	let i = match parse_radix_literal(literal) {
+		Some(bits) if bits.bits() <= ctx.int_type.bits() as u64 => ctx.int_type.wrap(&bits),
+		Some(bits) => bits,
+		None => {
+			let r = rational::parse_rational(literal);
+			if !r.is_integer() {
+				return Err(integer_error(literal));
+			}
+			r.to_integer()
+		}
+	};
+	fit(if negative { -i } else { i }, ctx).map(Value::Integer)
+}
+
+/// Performs an arithmetic or bitwise operation on two values that aren't
+/// lists
+pub(crate) fn fixed_op(lhs: &Value, op: Operation, rhs: &Value, ctx: &Context) -> Result<Value, CalcError> {
+	let (lhs, rhs) = (to_integer(lhs)?, to_integer(rhs)?);
+	if matches!(op, Operation::Div | Operation::FloorDiv | Operation::Mod) && rhs.is_zero() {
+		return Err(CalcError::new(CalcErrorType::CalculationError, format!("Can't divide {lhs} by zero")));
+	}
+	let res = match op {
+		Operation::Add => lhs + rhs,
+		Operation::Sub => lhs - rhs,
+		Operation::Mul => lhs * rhs,
+		Operation::Div => lhs / rhs,
+		Operation::FloorDiv => lhs.div_floor(&rhs),
+		Operation::Mod => lhs % rhs,
+		Operation::Exp => return pow(&lhs, &rhs, ctx).map(Value::Integer),
+		_ => {
+			// shifting by the width of the type or more shifts every bit out
+			let width = BigInt::from(ctx.int_type.bits());
+			let rhs = if matches!(op, Operation::Shl | Operation::Shr) && rhs > width { width } else { rhs };
+			let res = integer::bitwise_op(&lhs, op, &rhs)?;
+			return Ok(Value::Integer(ctx.int_type.wrap(&res)));
+		}
+	};
+	fit(res, ctx).map(Value::Integer)
+}
+
+/// Raises an integer to a non-negative integer power
+fn pow(base: &BigInt, exp: &BigInt, ctx: &Context) -> Result<BigInt, CalcError> {
+	if exp.is_negative() {
+		return Err(CalcError::new(CalcErrorType::CalculationError, format!("Can't raise {base} to the negative power {exp} in programmer mode")));
+	}
+	if ctx.overflow_mode == OverflowMode::Wrap {
+		let modulus = BigInt::one() << ctx.int_type.bits();
+		return Ok(ctx.int_type.wrap(&base.modpow(exp, &modulus)));
+	}
+	// powers of numbers other than -1, 0 and 1 outgrow every type quickly
+	if base.abs() <= BigInt::one() {
+		return Ok(if exp.is_zero() { BigInt::one() } else if exp.is_even() { base.abs() } else { base.clone() });
+	}
+	match exp.to_u32() {
+		Some(n) if n <= ctx.int_type.bits() => fit(base.pow(n), ctx),
+		_ => Err(overflow_error(format!("{base}^{exp}"), ctx)),
+	}
+}
+
+/// Computes the factorial of a non-negative integer
+pub(crate) fn factorial(val: &Value, ctx: &Context) -> Result<Value, CalcError> {
+	let n = to_integer(val)?;
+	if n.is_negative() {
+		return Err(CalcError::new(CalcErrorType::CalculationError, format!("Can't take the factorial of the negative integer {n}")));
+	}
+	match integer::factorial(&n) {
+		Some(res) => fit(res, ctx).map(Value::Integer),
+		// larger factorials are multiples of every power of two a type can hold
+		None if ctx.overflow_mode == OverflowMode::Wrap => Ok(Value::Integer(BigInt::zero())),
+		None => Err(overflow_error(format!("{n}!"), ctx)),
+	}
+}
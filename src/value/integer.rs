@@ -0,0 +1,82 @@
+//! Exact integer arithmetic used in real mode
+//!
+//! Integer literals are kept as unbounded integers for as long as operations
+//! have integer results, so `30!` or `2^100` are computed exactly. Operations
+//! without an integer result return None so the caller can fall back to floats.
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{CalcError, CalcErrorType};
+use crate::ast::Operation;
+
+/// Largest number of bits in an exact power, bigger powers fall back to floats
+pub(crate) const MAX_EXACT_POWER_BITS: u64 = 1 << 20;
+
+/// Largest integer for which factorials are calculated exactly
+pub(crate) const MAX_EXACT_FACTORIAL: u64 = 10_000;
+
+/// Performs a binary operation on two integers, returning None if the result
+/// isn't an integer or the divisor is zero
+pub(crate) fn integer_op(lhs: &BigInt, op: Operation, rhs: &BigInt) -> Option<BigInt> {
+	if matches!(op, Operation::Div | Operation::FloorDiv | Operation::Mod) && rhs.is_zero() {
+		return None;
+	}
+	match op {
+		Operation::Add => Some(lhs + rhs),
+		Operation::Sub => Some(lhs - rhs),
+		Operation::Mul => Some(lhs * rhs),
+		Operation::Div => {
+			let (quotient, remainder) = lhs.div_rem(rhs);
+			remainder.is_zero().then_some(quotient)
+		}
+		Operation::FloorDiv => Some(lhs.div_floor(rhs)),
+		// like the real `%`, the remainder takes the sign of the dividend
+		Operation::Mod => Some(lhs % rhs),
+		Operation::Exp => {
+			if rhs.is_negative() {
+				return None;
+			}
+			let n = rhs.to_u32()?;
+			if lhs.bits().saturating_mul(n as u64) > MAX_EXACT_POWER_BITS {
+				return None;
+			}
+			Some(lhs.pow(n))
+		}
+		_ => unreachable!("Logical and bitwise operations are evaluated separately"),
+	}
+}
+
+/// Performs a bitwise operation on two integers, negative numbers behaving as
+/// if they were in two's complement with infinitely many bits
+pub(crate) fn bitwise_op(lhs: &BigInt, op: Operation, rhs: &BigInt) -> Result<BigInt, CalcError> {
+	match op {
+		Operation::BitAnd => Ok(lhs & rhs),
+		Operation::BitOr => Ok(lhs | rhs),
+		Operation::BitXor => Ok(lhs ^ rhs),
+		Operation::Shl | Operation::Shr => {
+			if rhs.is_negative() {
+				return Err(CalcError::new(CalcErrorType::CalculationError, format!("Can't shift by {rhs} bits, the shift must not be negative")));
+			}
+			let shift = rhs.to_u64();
+			if op == Operation::Shr {
+				// shifting right by more bits than there are leaves the sign
+				return Ok(shift.map_or_else(|| if lhs.is_negative() { -BigInt::one() } else { BigInt::zero() }, |shift| lhs >> shift));
+			}
+			match shift {
+				_ if lhs.is_zero() => Ok(BigInt::zero()),
+				Some(shift) if lhs.bits().saturating_add(shift) <= MAX_EXACT_POWER_BITS => Ok(lhs << shift),
+				_ => Err(CalcError::new(CalcErrorType::CalculationError, "Result is too large to represent")),
+			}
+		}
+		_ => unreachable!("Not a bitwise operation"),
+	}
+}
+
+/// Factorial of a non-negative integer, returning None for negative or very
+/// large numbers
+pub(crate) fn factorial(n: &BigInt) -> Option<BigInt> {
+	let n = n.to_u64().filter(|n| *n <= MAX_EXACT_FACTORIAL)?;
+	Some((2..=n).fold(BigInt::one(), |acc, i| acc * i))
+}
@@ -0,0 +1,115 @@
+//! Lists of values, written as `[1, 2, 3]`
+//!
+//! Operations on lists are applied element-wise, broadcasting single values
+//! against every element, so `[1, 2] * 2` is `[2, 4]`. Matrices are indexed
+//! and sliced as lists of rows.
+
+use crate::{CalcError, CalcErrorType};
+use super::{Value, matrix};
+
+fn list_error(msg: String) -> CalcError {
+	CalcError::new(CalcErrorType::CalculationError, msg)
+}
+
+/// Pairs up the elements of lists given as arguments, repeating arguments that
+/// aren't lists. All lists must have the same length
+pub(crate) fn broadcast(args: Vec<Value>) -> Result<Vec<Vec<Value>>, CalcError> {
+	let mut len: Option<usize> = None;
+	for arg in &args {
+		if let Value::List(list) = arg {
+			match len {
+				Some(n) if n != list.len() => {
+					return Err(list_error(format!("Can't combine lists of lengths {n} and {}", list.len())));
+				}
+				_ => len = Some(list.len()),
+			}
+		}
+	}
+	let len = len.unwrap_or(1);
+	Ok((0..len).map(|i| args.iter().map(|arg| match arg {
+		Value::List(list) => list[i].clone(),
+		arg => arg.clone(),
+	}).collect()).collect())
+}
+
+/// Applies an operation to every combination of elements given by
+/// `broadcast()`, collecting the results into a list
+pub(crate) fn map(args: Vec<Value>, f: impl Fn(Vec<Value>) -> Result<Value, CalcError>) -> Result<Value, CalcError> {
+	let res = broadcast(args)?.into_iter().map(f).collect::<Result<_, _>>()?;
+	Ok(Value::List(res))
+}
+
+/// Replaces lists with their elements, used for functions that take any number
+/// of arguments
+pub(crate) fn flatten(args: Vec<Value>) -> Vec<Value> {
+	let mut res = Vec::with_capacity(args.len());
+	for arg in args {
+		match arg {
+			Value::List(list) => res.extend(flatten(list)),
+			Value::Matrix(m) => res.extend(m.elements().map(Value::Real)),
+			arg => res.push(arg),
+		}
+	}
+	res
+}
+
+/// `len(list)`, the number of elements of a list, or of rows of a matrix
+pub(crate) fn len(mut args: Vec<Value>) -> Result<Value, CalcError> {
+	match args.remove(0) {
+		Value::List(list) => Ok(Value::Integer(list.len().into())),
+		Value::Matrix(m) => Ok(Value::Integer(m.rows().into())),
+		val => Err(list_error(format!("Can't take the length of {val}, it's not a list"))),
+	}
+}
+
+/// Converts an index into a position in a list, counting from the end for
+/// negative indices. Positions outside of the list are kept, clamping is left
+/// to the caller
+fn position(index: &Value, len: usize) -> Result<i64, CalcError> {
+	let i = match index.to_f64() {
+		Some(i) if i.fract() == 0.0 && i.is_finite() => i as i64,
+		_ => return Err(list_error(format!("List index {index} is not an integer"))),
+	};
+	Ok(if i < 0 { i + len as i64 } else { i })
+}
+
+fn as_list(val: Value) -> Result<Vec<Value>, CalcError> {
+	match val {
+		Value::List(list) => Ok(list),
+		Value::Matrix(m) => Ok(m.into_rows()),
+		val => Err(list_error(format!("Can't index {val}, it's not a list"))),
+	}
+}
+
+/// Returns the element of a list at an index, starting from 0
+pub(crate) fn index(list: Value, index: &Value) -> Result<Value, CalcError> {
+	let mut list = as_list(list)?;
+	let len = list.len();
+	match position(index, len)? {
+		i if (0..len as i64).contains(&i) => Ok(list.swap_remove(i as usize)),
+		_ => Err(list_error(format!("List index {index} is out of range for a list of length {len}"))),
+	}
+}
+
+/// Returns the elements of a list from `start` up to but excluding `end`,
+/// bounds default to the start and end of the list
+pub(crate) fn slice(list: Value, start: Option<&Value>, end: Option<&Value>) -> Result<Value, CalcError> {
+	let is_matrix = matches!(list, Value::Matrix(_));
+	let list = as_list(list)?;
+	let len = list.len();
+	let clamp = |i: i64| i.clamp(0, len as i64) as usize;
+	let start = match start {
+		Some(i) => clamp(position(i, len)?),
+		None => 0,
+	};
+	let end = match end {
+		Some(i) => clamp(position(i, len)?),
+		None => len,
+	};
+	let res: Vec<Value> = list.into_iter().take(end).skip(start).collect();
+	// slices of the rows of a matrix are matrices
+	if is_matrix && !res.is_empty() {
+		return matrix::from_list(res);
+	}
+	Ok(Value::List(res))
+}
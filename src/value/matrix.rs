@@ -0,0 +1,555 @@
+//! Matrices of real numbers and linear algebra
+//!
+//! A list literal whose items are all lists of the same length, such as
+//! `[[1, 2], [3, 4]]`, is a matrix. Matrices hold real numbers, `*` between two
+//! matrices is the matrix product, and lists combined with matrices are treated
+//! as vectors.
+
+use std::fmt;
+use std::ops::{Div, Mul, Neg, Sub};
+use num_rational::BigRational;
+use num_traits::{One, ToPrimitive, Zero};
+use crate::{CalcError, CalcErrorType};
+use crate::ast::Operation;
+use super::{Value, real_op, rational};
+
+/// Largest number of rows or columns of the matrices built by `identity()` and
+/// `zeros()`
+const MAX_SIZE: usize = 1000;
+
+/// Largest number of elements of a matrix reduced with exact fractions, so that
+/// results like `inv([[1, 2], [3, 4]])` have no rounding errors. Larger matrices
+/// are reduced with floating point numbers
+const MAX_EXACT_ELEMENTS: usize = 512;
+
+/// Largest number of sweeps of the Jacobi eigenvalue method, which converges
+/// long before this for symmetric matrices
+const MAX_SWEEPS: usize = 100;
+
+/// Matrix of real numbers, stored row by row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+	rows: usize,
+	cols: usize,
+	data: Vec<f64>,
+}
+
+impl Matrix {
+	/// Creates a matrix from its rows, returning None if there are no rows, or
+	/// if they are empty or of different lengths
+	pub fn from_rows(rows: Vec<Vec<f64>>) -> Option<Self> {
+		let cols = rows.first()?.len();
+		if cols == 0 || rows.iter().any(|row| row.len() != cols) {
+			return None;
+		}
+		Some(Self { rows: rows.len(), cols, data: rows.concat() })
+	}
+
+	fn zeros(rows: usize, cols: usize) -> Self {
+		Self { rows, cols, data: vec![0.0; rows * cols] }
+	}
+
+	fn identity(n: usize) -> Self {
+		let mut res = Self::zeros(n, n);
+		for i in 0..n {
+			res[(i, i)] = 1.0;
+		}
+		res
+	}
+
+	/// Number of rows
+	pub fn rows(&self) -> usize {
+		self.rows
+	}
+
+	/// Number of columns
+	pub fn cols(&self) -> usize {
+		self.cols
+	}
+
+	/// Returns the element at a row and column, starting from 0
+	pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+		(row < self.rows && col < self.cols).then(|| self[(row, col)])
+	}
+
+	/// Returns the rows of the matrix as lists
+	pub(crate) fn into_rows(self) -> Vec<Value> {
+		self.data.chunks(self.cols).map(|row| Value::List(row.iter().map(|&n| Value::Real(n)).collect())).collect()
+	}
+
+	/// Returns the elements of the matrix, row by row
+	pub(crate) fn elements(&self) -> impl Iterator<Item = f64> + '_ {
+		self.data.iter().copied()
+	}
+
+	fn map(mut self, f: impl Fn(f64) -> f64) -> Self {
+		self.data.iter_mut().for_each(|n| *n = f(*n));
+		self
+	}
+
+	fn transpose(&self) -> Self {
+		let mut res = Self::zeros(self.cols, self.rows);
+		for i in 0..self.rows {
+			for j in 0..self.cols {
+				res[(j, i)] = self[(i, j)];
+			}
+		}
+		res
+	}
+
+	fn mul(&self, rhs: &Self) -> Result<Self, CalcError> {
+		if self.cols != rhs.rows {
+			return Err(matrix_error(format!("Can't multiply a {} matrix by a {} matrix", self.shape(), rhs.shape())));
+		}
+		let mut res = Self::zeros(self.rows, rhs.cols);
+		for i in 0..self.rows {
+			for j in 0..rhs.cols {
+				res[(i, j)] = (0..self.cols).map(|k| self[(i, k)] * rhs[(k, j)]).sum();
+			}
+		}
+		Ok(res)
+	}
+
+	fn shape(&self) -> String {
+		format!("{}x{}", self.rows, self.cols)
+	}
+
+	fn require_square(&self, name: &str) -> Result<(), CalcError> {
+		if self.rows != self.cols {
+			return Err(matrix_error(format!("\"{name}()\" requires a square matrix, got a {} matrix", self.shape())));
+		}
+		Ok(())
+	}
+
+	/// Elements smaller than this are treated as zero when eliminating
+	fn tolerance(&self) -> f64 {
+		let largest = self.data.iter().fold(0.0, |max: f64, n| max.max(n.abs()));
+		f64::EPSILON * self.rows.max(self.cols) as f64 * largest
+	}
+
+	/// Rows of the matrix as exact fractions, if it's small enough and all of
+	/// its elements are finite. Elements are converted from their shortest
+	/// representation, so `0.1` becomes exactly `1/10`
+	fn exact_rows(&self) -> Option<Vec<Vec<BigRational>>> {
+		if self.data.len() > MAX_EXACT_ELEMENTS {
+			return None;
+		}
+		let exact = |n: f64| Value::Real(n).to_decimal().map(|d| rational::decimal_to_rational(&d));
+		self.data.chunks(self.cols).map(|row| row.iter().map(|&n| exact(n)).collect()).collect()
+	}
+
+	fn float_rows(&self) -> Vec<Vec<f64>> {
+		self.data.chunks(self.cols).map(<[f64]>::to_vec).collect()
+	}
+
+	fn det(&self) -> f64 {
+		match self.exact_rows() {
+			Some(rows) => determinant(rows, 0.0).real(),
+			None => determinant(self.float_rows(), self.tolerance()),
+		}
+	}
+
+	fn rank(&self) -> usize {
+		match self.exact_rows() {
+			Some(mut rows) => echelon(&mut rows, 0.0).0,
+			None => echelon(&mut self.float_rows(), self.tolerance()).0,
+		}
+	}
+
+	/// Solves `self * x = rhs` for every column of `rhs`, failing if the matrix
+	/// is singular
+	fn solve(&self, rhs: &Self) -> Result<Self, CalcError> {
+		if self.rows != rhs.rows {
+			return Err(matrix_error(format!("Can't solve a system with a {} matrix and {} right-hand side values", self.shape(), rhs.rows)));
+		}
+		let n = self.rows;
+		let mut augmented = Self::zeros(n, n + rhs.cols);
+		for i in 0..n {
+			for j in 0..n {
+				augmented[(i, j)] = self[(i, j)];
+			}
+			for j in 0..rhs.cols {
+				augmented[(i, n + j)] = rhs[(i, j)];
+			}
+		}
+		let solved = match augmented.exact_rows() {
+			Some(mut rows) => gauss_jordan(&mut rows, n, 0.0).then(|| rows.iter().map(|row| row.iter().map(Element::real).collect()).collect()),
+			None => {
+				let mut rows = augmented.float_rows();
+				gauss_jordan(&mut rows, n, self.tolerance()).then_some(rows)
+			}
+		};
+		let Some(solved) = solved else {
+			return Err(matrix_error("The matrix is singular".to_string()));
+		};
+		let solution = solved.into_iter().map(|row: Vec<f64>| row[n..].to_vec()).collect();
+		Ok(Self::from_rows(solution).expect("Solution has the shape of the right-hand side"))
+	}
+
+	fn inv(&self) -> Result<Self, CalcError> {
+		self.require_square("inv")?;
+		self.solve(&Self::identity(self.rows))
+	}
+
+	fn pow(&self, exp: f64) -> Result<Self, CalcError> {
+		if self.rows != self.cols {
+			return Err(matrix_error(format!("Can't raise a {} matrix to a power, it isn't square", self.shape())));
+		}
+		if exp.fract() != 0.0 || !exp.is_finite() {
+			return Err(matrix_error(format!("Matrices can only be raised to integer powers, not {exp}")));
+		}
+		let mut base = if exp < 0.0 { self.inv()? } else { self.clone() };
+		let mut exp = exp.abs() as u64;
+		let mut res = Self::identity(self.rows);
+		while exp > 0 {
+			if exp & 1 == 1 {
+				res = res.mul(&base)?;
+			}
+			base = base.mul(&base)?;
+			exp >>= 1;
+		}
+		Ok(res)
+	}
+
+	/// Computes the eigenvalues of a symmetric matrix with the Jacobi method,
+	/// in ascending order
+	fn eigenvalues(&self) -> Result<Vec<f64>, CalcError> {
+		self.require_square("eig")?;
+		let n = self.rows;
+		let tolerance = self.tolerance().max(f64::MIN_POSITIVE);
+		if (0..n).any(|i| (0..i).any(|j| (self[(i, j)] - self[(j, i)]).abs() > tolerance)) {
+			return Err(matrix_error("\"eig()\" only supports symmetric matrices".to_string()));
+		}
+		if n == 2 {
+			// closed form, exact for simple matrices
+			let mean = (self[(0, 0)] + self[(1, 1)]) / 2.0;
+			let radius = ((self[(0, 0)] - self[(1, 1)]) / 2.0).hypot(self[(0, 1)]);
+			return Ok(vec![mean - radius, mean + radius]);
+		}
+		let mut a = self.clone();
+		for _ in 0..MAX_SWEEPS {
+			let off_diagonal: f64 = (0..n).flat_map(|i| (0..i).map(move |j| (i, j))).map(|(i, j)| a[(i, j)].powi(2)).sum();
+			if off_diagonal.sqrt() <= tolerance {
+				break;
+			}
+			for p in 0..n {
+				for q in p + 1..n {
+					if a[(p, q)] == 0.0 {
+						continue;
+					}
+					// rotation zeroing the element at (p, q)
+					let theta = (a[(q, q)] - a[(p, p)]) / (2.0 * a[(p, q)]);
+					let t = theta.signum() / (theta.abs() + theta.hypot(1.0));
+					let c = 1.0 / t.hypot(1.0);
+					let s = t * c;
+					for k in 0..n {
+						let (kp, kq) = (a[(k, p)], a[(k, q)]);
+						a[(k, p)] = c * kp - s * kq;
+						a[(k, q)] = s * kp + c * kq;
+					}
+					for k in 0..n {
+						let (pk, qk) = (a[(p, k)], a[(q, k)]);
+						a[(p, k)] = c * pk - s * qk;
+						a[(q, k)] = s * pk + c * qk;
+					}
+				}
+			}
+		}
+		let mut res: Vec<f64> = (0..n).map(|i| a[(i, i)]).collect();
+		res.sort_by(f64::total_cmp);
+		Ok(res)
+	}
+}
+
+/// Number that matrices can be reduced with
+trait Element: Clone + Zero + One + Neg<Output = Self>
+	+ for<'a> Sub<&'a Self, Output = Self> + for<'a> Mul<&'a Self, Output = Self> + for<'a> Div<&'a Self, Output = Self> {
+	/// Size of the number, used to pick pivots
+	fn magnitude(&self) -> f64;
+	/// Returns true if the number counts as zero, either exactly or within a
+	/// tolerance for rounding errors
+	fn is_negligible(&self, tolerance: f64) -> bool;
+	fn real(&self) -> f64;
+}
+
+impl Element for f64 {
+	fn magnitude(&self) -> f64 {
+		self.abs()
+	}
+
+	fn is_negligible(&self, tolerance: f64) -> bool {
+		self.abs() <= tolerance
+	}
+
+	fn real(&self) -> f64 {
+		*self
+	}
+}
+
+impl Element for BigRational {
+	fn magnitude(&self) -> f64 {
+		self.to_f64().map_or(f64::INFINITY, f64::abs)
+	}
+
+	fn is_negligible(&self, _: f64) -> bool {
+		self.is_zero()
+	}
+
+	fn real(&self) -> f64 {
+		self.to_f64().unwrap_or(f64::NAN)
+	}
+}
+
+/// Finds the row from `start` with the largest element in a column
+fn pivot<E: Element>(rows: &[Vec<E>], start: usize, col: usize) -> usize {
+	(start..rows.len()).max_by(|&a, &b| rows[a][col].magnitude().total_cmp(&rows[b][col].magnitude())).expect("At least one row left")
+}
+
+/// Reduces rows to row echelon form with partial pivoting, returning the number
+/// of pivots and whether an odd number of rows were swapped
+fn echelon<E: Element>(rows: &mut [Vec<E>], tolerance: f64) -> (usize, bool) {
+	let cols = rows[0].len();
+	let mut rank = 0;
+	let mut swapped = false;
+	for col in 0..cols {
+		if rank == rows.len() {
+			break;
+		}
+		let best = pivot(rows, rank, col);
+		if rows[best][col].is_negligible(tolerance) {
+			continue;
+		}
+		if best != rank {
+			rows.swap(best, rank);
+			swapped = !swapped;
+		}
+		let (top, below) = rows.split_at_mut(rank + 1);
+		let pivot_row = &top[rank];
+		for row in below {
+			let factor = row[col].clone() / &pivot_row[col];
+			for (item, pivot_item) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+				*item = item.clone() - &(factor.clone() * pivot_item);
+			}
+		}
+		rank += 1;
+	}
+	(rank, swapped)
+}
+
+fn determinant<E: Element>(mut rows: Vec<Vec<E>>, tolerance: f64) -> E {
+	let n = rows.len();
+	let (rank, swapped) = echelon(&mut rows, tolerance);
+	if rank < n {
+		return E::zero();
+	}
+	let det = (0..n).fold(E::one(), |det, i| det * &rows[i][i]);
+	if swapped { -det } else { det }
+}
+
+/// Reduces the first `n` columns of rows to the identity matrix with
+/// Gauss-Jordan elimination, returning false if they're singular
+fn gauss_jordan<E: Element>(rows: &mut [Vec<E>], n: usize, tolerance: f64) -> bool {
+	for col in 0..n {
+		let best = pivot(rows, col, col);
+		if rows[best][col].is_negligible(tolerance) {
+			return false;
+		}
+		rows.swap(best, col);
+		let pivot = rows[col][col].clone();
+		for item in rows[col].iter_mut() {
+			*item = item.clone() / &pivot;
+		}
+		for i in (0..rows.len()).filter(|&i| i != col) {
+			let factor = rows[i][col].clone();
+			if factor.is_zero() {
+				continue;
+			}
+			for j in 0..rows[i].len() {
+				let delta = factor.clone() * &rows[col][j];
+				rows[i][j] = rows[i][j].clone() - &delta;
+			}
+		}
+	}
+	true
+}
+
+impl std::ops::Index<(usize, usize)> for Matrix {
+	type Output = f64;
+	fn index(&self, (row, col): (usize, usize)) -> &f64 {
+		&self.data[row * self.cols + col]
+	}
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Matrix {
+	fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut f64 {
+		&mut self.data[row * self.cols + col]
+	}
+}
+
+impl fmt::Display for Matrix {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		let rows: Vec<String> = self.data.chunks(self.cols).map(|row| {
+			let items: Vec<String> = row.iter().map(f64::to_string).collect();
+			format!("[{}]", items.join(", "))
+		}).collect();
+		write!(formatter, "[{}]", rows.join(", "))
+	}
+}
+
+fn matrix_error(msg: String) -> CalcError {
+	CalcError::new(CalcErrorType::MatrixError, msg)
+}
+
+/// Returns a value as a real number that can be stored in a matrix
+fn to_element(val: &Value) -> Result<f64, CalcError> {
+	match val {
+		Value::Real(_) | Value::Integer(_) | Value::Decimal(_) | Value::Rational(_) => Ok(val.to_f64().unwrap_or(f64::NAN)),
+		_ => Err(matrix_error(format!("Matrices can only hold real numbers, not {val}"))),
+	}
+}
+
+/// Turns a list or a number into a column vector
+fn to_column(val: &Value) -> Result<Matrix, CalcError> {
+	let items = match val {
+		Value::List(list) => list.iter().map(to_element).collect::<Result<Vec<_>, _>>()?,
+		val => vec![to_element(val)?],
+	};
+	Matrix::from_rows(items.into_iter().map(|n| vec![n]).collect()).ok_or_else(|| matrix_error("Vectors can't be empty".to_string()))
+}
+
+fn column_to_list(m: Matrix) -> Value {
+	Value::List(m.data.into_iter().map(Value::Real).collect())
+}
+
+/// Turns a value into a matrix, numbers being 1x1 matrices
+fn to_matrix(val: Value) -> Result<Matrix, CalcError> {
+	match val {
+		Value::Matrix(m) => Ok(m),
+		val => Ok(Matrix { rows: 1, cols: 1, data: vec![to_element(&val)?] }),
+	}
+}
+
+/// Builds the value of a list literal, which is a matrix if all of its items
+/// are lists
+pub(crate) fn from_list(items: Vec<Value>) -> Result<Value, CalcError> {
+	if items.is_empty() || !items.iter().all(|item| matches!(item, Value::List(_))) {
+		return Ok(Value::List(items));
+	}
+	let rows = items.iter().map(|row| match row {
+		Value::List(row) => row.iter().map(to_element).collect::<Result<Vec<_>, _>>(),
+		_ => unreachable!("All items are lists"),
+	}).collect::<Result<Vec<_>, _>>()?;
+	let m = Matrix::from_rows(rows).ok_or_else(|| matrix_error("Rows of a matrix must be non-empty and of the same length".to_string()))?;
+	Ok(Value::Matrix(m))
+}
+
+/// Performs a binary operation where at least one side is a matrix
+pub(crate) fn matrix_op(lhs: Value, op: Operation, rhs: Value) -> Result<Value, CalcError> {
+	match (lhs, op, rhs) {
+		(lhs, op, rhs) if op.is_bitwise() => Err(matrix_error(format!("Can't apply {op} to {lhs} and {rhs}, matrices hold real numbers"))),
+		(Value::Matrix(lhs), op, Value::Matrix(rhs)) if matches!(op, Operation::Add | Operation::Sub) || op.is_logical() => {
+			if lhs.rows != rhs.rows || lhs.cols != rhs.cols {
+				return Err(matrix_error(format!("Can't combine a {} matrix with a {} matrix", lhs.shape(), rhs.shape())));
+			}
+			let data = lhs.data.iter().zip(&rhs.data).map(|(&a, &b)| real_op(a, op, b)).collect();
+			Ok(Value::Matrix(Matrix { data, ..lhs }))
+		}
+		(Value::Matrix(lhs), Operation::Mul, Value::Matrix(rhs)) => Ok(Value::Matrix(lhs.mul(&rhs)?)),
+		// lists are column vectors on the right and row vectors on the left
+		(Value::Matrix(lhs), Operation::Mul, rhs @ Value::List(_)) => Ok(column_to_list(lhs.mul(&to_column(&rhs)?)?)),
+		(lhs @ Value::List(_), Operation::Mul, Value::Matrix(rhs)) => Ok(column_to_list(rhs.transpose().mul(&to_column(&lhs)?)?)),
+		(lhs @ (Value::Matrix(_) | Value::List(_)), _, rhs @ (Value::Matrix(_) | Value::List(_))) => {
+			Err(matrix_error(format!("Can't apply {op} to {lhs} and {rhs}")))
+		}
+		(Value::Matrix(lhs), Operation::Exp, rhs) => Ok(Value::Matrix(lhs.pow(to_element(&rhs)?)?)),
+		(Value::Matrix(lhs), _, rhs) => {
+			let rhs = to_element(&rhs)?;
+			Ok(Value::Matrix(lhs.map(|n| real_op(n, op, rhs))))
+		}
+		(lhs, op, Value::Matrix(rhs)) if matches!(op, Operation::Add | Operation::Sub | Operation::Mul) || op.is_logical() => {
+			let lhs = to_element(&lhs)?;
+			Ok(Value::Matrix(rhs.map(|n| real_op(lhs, op, n))))
+		}
+		(lhs, _, rhs) => Err(matrix_error(format!("Can't apply {op} to {lhs} and a matrix {rhs}"))),
+	}
+}
+
+/// Negates every element of a matrix
+pub(crate) fn negate(m: Matrix) -> Matrix {
+	m.map(|n| -n)
+}
+
+/// Turns a number into the size of a matrix
+fn to_size(val: &Value, name: &str) -> Result<usize, CalcError> {
+	match val.to_f64() {
+		Some(n) if n.fract() == 0.0 && (1.0..=MAX_SIZE as f64).contains(&n) => Ok(n as usize),
+		_ => Err(CalcError::new(CalcErrorType::ArgumentError, format!("\"{name}()\" takes sizes from 1 to {MAX_SIZE}, got {val}"))),
+	}
+}
+
+/// `transpose(A)`
+pub(crate) fn transpose(mut args: Vec<Value>) -> Result<Value, CalcError> {
+	match args.remove(0) {
+		Value::List(list) => Ok(Value::Matrix(to_column(&Value::List(list))?.transpose())),
+		val => Ok(Value::Matrix(to_matrix(val)?.transpose())),
+	}
+}
+
+/// `det(A)`
+pub(crate) fn det(mut args: Vec<Value>) -> Result<Value, CalcError> {
+	let m = to_matrix(args.remove(0))?;
+	m.require_square("det")?;
+	Ok(Value::Real(m.det()))
+}
+
+/// `inv(A)`
+pub(crate) fn inv(mut args: Vec<Value>) -> Result<Value, CalcError> {
+	let m = to_matrix(args.remove(0))?;
+	Ok(Value::Matrix(m.inv()?))
+}
+
+/// `rank(A)`
+pub(crate) fn rank(mut args: Vec<Value>) -> Result<Value, CalcError> {
+	let m = to_matrix(args.remove(0))?;
+	Ok(Value::Integer(m.rank().into()))
+}
+
+/// `trace(A)`
+pub(crate) fn trace(mut args: Vec<Value>) -> Result<Value, CalcError> {
+	let m = to_matrix(args.remove(0))?;
+	m.require_square("trace")?;
+	Ok(Value::Real((0..m.rows).map(|i| m[(i, i)]).sum()))
+}
+
+/// `identity(n)`
+pub(crate) fn identity(args: Vec<Value>) -> Result<Value, CalcError> {
+	Ok(Value::Matrix(Matrix::identity(to_size(&args[0], "identity")?)))
+}
+
+/// `zeros(rows, cols)`, square if the number of columns is left out
+pub(crate) fn zeros(args: Vec<Value>) -> Result<Value, CalcError> {
+	let rows = to_size(&args[0], "zeros")?;
+	let cols = match args.get(1) {
+		Some(cols) => to_size(cols, "zeros")?,
+		None => rows,
+	};
+	Ok(Value::Matrix(Matrix::zeros(rows, cols)))
+}
+
+/// `solve(A, b)`, the solution of `A x = b` where `b` is a vector or a matrix
+pub(crate) fn solve(mut args: Vec<Value>) -> Result<Value, CalcError> {
+	let rhs = args.pop().expect("Two arguments");
+	let m = to_matrix(args.pop().expect("Two arguments"))?;
+	m.require_square("solve")?;
+	match rhs {
+		Value::Matrix(rhs) => Ok(Value::Matrix(m.solve(&rhs)?)),
+		Value::List(list) => Ok(column_to_list(m.solve(&to_column(&Value::List(list))?)?)),
+		rhs => Ok(Value::Real(m.solve(&to_column(&rhs)?)?.data[0])),
+	}
+}
+
+/// `eig(A)`, the eigenvalues of a symmetric matrix in ascending order
+pub(crate) fn eig(mut args: Vec<Value>) -> Result<Value, CalcError> {
+	let m = to_matrix(args.remove(0))?;
+	Ok(Value::List(m.eigenvalues()?.into_iter().map(Value::Real).collect()))
+}
@@ -0,0 +1,555 @@
+//! Values produced by evaluating expressions
+//!
+//! Every expression evaluates to a `Value`. By default this is a real number,
+//! or an exact integer for integer arithmetic. Complex, decimal and rational
+//! numbers only appear when the `Context` is set to the matching `NumberMode`.
+//! Numbers written with a unit, such as `5 m`, are quantities, and values can
+//! be grouped into lists such as `[1, 2, 3]`.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use libm::tgamma;
+
+pub use num_complex::Complex64;
+pub use bigdecimal::BigDecimal;
+pub use num_rational::BigRational;
+pub use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use crate::{CalcError, CalcErrorType};
+use crate::ast::Operation;
+use crate::context::{Context, NumberMode, AngleMode};
+
+mod complex;
+pub(crate) use complex::*;
+
+pub(crate) mod decimal;
+
+pub(crate) mod rational;
+
+pub(crate) mod integer;
+
+pub(crate) mod fixed;
+
+pub(crate) mod strict;
+
+pub(crate) mod units;
+
+pub(crate) mod list;
+
+pub(crate) mod matrix;
+pub use matrix::Matrix;
+pub use units::{Quantity, Dimension};
+pub use rational::RationalFormat;
+
+#[cfg(test)]
+mod tests;
+
+/// A number produced by a calculation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	/// Real number
+	Real(f64),
+	/// Exact integer, produced by integer arithmetic in real mode and by every
+	/// operation in programmer mode
+	Integer(BigInt),
+	/// Complex number with a non-zero imaginary part
+	Complex(Complex64),
+	/// Arbitrary-precision decimal number
+	Decimal(BigDecimal),
+	/// Exact fraction of two integers
+	Rational(BigRational),
+	/// Real number with a physical unit
+	Quantity(Quantity),
+	/// List of values
+	List(Vec<Value>),
+	/// Matrix of real numbers
+	Matrix(Matrix),
+}
+
+impl Value {
+	/// Returns the value as a real number, or None if it has an imaginary part
+	pub fn to_f64(&self) -> Option<f64> {
+		match self {
+			Self::Real(n) => Some(*n),
+			Self::Integer(i) => i.to_f64(),
+			Self::Complex(_) => None,
+			Self::Decimal(d) => d.to_f64(),
+			Self::Rational(r) => r.to_f64(),
+			Self::Quantity(q) => Some(q.value()),
+			Self::List(_) | Self::Matrix(_) => None,
+		}
+	}
+
+	/// Returns the value as a complex number, NaN for lists and matrices
+	pub fn to_complex(&self) -> Complex64 {
+		match self {
+			Self::Complex(z) => *z,
+			_ => Complex64::new(self.to_f64().unwrap_or(f64::NAN), 0.0),
+		}
+	}
+
+	/// Returns the value as a decimal number, or None if it's complex or not
+	/// finite. Real numbers are converted using their shortest representation,
+	/// so `0.1` becomes exactly `0.1`, and rationals to 100 significant digits
+	pub fn to_decimal(&self) -> Option<BigDecimal> {
+		match self {
+			Self::Real(n) if n.is_finite() => BigDecimal::from_str(&n.to_string()).ok(),
+			Self::Decimal(d) => Some(d.clone()),
+			Self::Rational(r) => Some(rational::to_decimal(r, 100)),
+			Self::Integer(i) => Some(BigDecimal::from(i.clone())),
+			_ => None,
+		}
+	}
+
+	/// Returns the value as an exact fraction, or None if it's not a rational
+	/// or an integer
+	pub(crate) fn to_rational(&self) -> Option<BigRational> {
+		match self {
+			Self::Rational(r) => Some(r.clone()),
+			Self::Integer(i) => Some(BigRational::from_integer(i.clone())),
+			_ => None,
+		}
+	}
+
+	/// Returns true if the value is NaN, or has a NaN component
+	pub fn is_nan(&self) -> bool {
+		match self {
+			Self::Real(n) => n.is_nan(),
+			Self::Complex(z) => z.is_nan(),
+			Self::Integer(_) | Self::Decimal(_) | Self::Rational(_) => false,
+			Self::Quantity(q) => q.magnitude.is_nan(),
+			Self::List(_) | Self::Matrix(_) => false,
+		}
+	}
+
+	/// Formats the value according to the settings of a context, such as the
+	/// format used for rationals
+	pub fn format(&self, ctx: &Context) -> String {
+		match self {
+			Self::Rational(r) => rational::format_rational(r, ctx.rational_format, ctx.decimal_precision()),
+			Self::List(list) => {
+				let items: Vec<String> = list.iter().map(|item| item.format(ctx)).collect();
+				format!("[{}]", items.join(", "))
+			}
+			_ => self.to_string(),
+		}
+	}
+
+	/// Turns a complex number without an imaginary part into a real number
+	pub(crate) fn simplify(self) -> Self {
+		match self {
+			Self::Complex(z) if z.im == 0.0 => Self::Real(z.re),
+			_ => self,
+		}
+	}
+}
+
+impl From<f64> for Value {
+	fn from(n: f64) -> Self {
+		Self::Real(n)
+	}
+}
+
+impl From<BigInt> for Value {
+	fn from(i: BigInt) -> Self {
+		Self::Integer(i)
+	}
+}
+
+impl From<Complex64> for Value {
+	fn from(z: Complex64) -> Self {
+		Self::Complex(z).simplify()
+	}
+}
+
+impl From<BigDecimal> for Value {
+	fn from(d: BigDecimal) -> Self {
+		Self::Decimal(d)
+	}
+}
+
+impl From<BigRational> for Value {
+	fn from(r: BigRational) -> Self {
+		Self::Rational(r)
+	}
+}
+
+impl From<Vec<Value>> for Value {
+	fn from(list: Vec<Value>) -> Self {
+		Self::List(list)
+	}
+}
+
+impl PartialEq<f64> for Value {
+	fn eq(&self, other: &f64) -> bool {
+		match self {
+			Self::Real(n) => n == other,
+			Self::Integer(i) => i.to_f64() == Some(*other),
+			Self::Complex(_) => false,
+			Self::Decimal(d) => d.to_f64() == Some(*other),
+			Self::Rational(r) => r.to_f64() == Some(*other),
+			Self::Quantity(_) | Self::List(_) | Self::Matrix(_) => false,
+		}
+	}
+}
+
+impl fmt::Display for Value {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Real(n) => write!(formatter, "{n}"),
+			Self::Integer(i) => write!(formatter, "{i}"),
+			Self::Complex(z) => write!(formatter, "{}", format_complex(*z)),
+			Self::Decimal(d) => write!(formatter, "{}", d.normalized()),
+			Self::Rational(r) => write!(formatter, "{r}"),
+			Self::Quantity(q) => write!(formatter, "{q}"),
+			Self::List(list) => {
+				let items: Vec<String> = list.iter().map(Value::to_string).collect();
+				write!(formatter, "[{}]", items.join(", "))
+			}
+			Self::Matrix(m) => write!(formatter, "{m}"),
+		}
+	}
+}
+
+/// Parses a number literal into the kind of number used by the number mode.
+/// In real mode, literals without a decimal point or exponent are parsed as
+/// exact integers if `exact_integers` is set. Hexadecimal, binary and octal
+/// literals such as `0xFF` are always integers
+pub(crate) fn parse_literal(literal: &str, ctx: &Context) -> Result<Value, CalcError> {
+	if ctx.number_mode == NumberMode::Programmer {
+		return fixed::parse_literal(literal, false, ctx);
+	}
+	Ok(match ctx.number_mode {
+		_ if parse_radix_literal(literal).is_some() => {
+			integer_value(parse_radix_literal(literal).expect("Failed to parse num literal"), ctx)
+		}
+		NumberMode::Decimal => Value::Decimal(BigDecimal::from_str(literal).expect("Failed to parse num literal")),
+		NumberMode::Rational => Value::Rational(rational::parse_rational(literal)),
+		NumberMode::Real if ctx.exact_integers && literal.bytes().all(|c| c.is_ascii_digit()) => {
+			Value::Integer(BigInt::from_str(literal).expect("Failed to parse num literal"))
+		}
+		_ => {
+			let n = literal.parse::<f64>().expect("Failed to parse num literal");
+			if ctx.strict_numerics {
+				strict::check_literal(literal, n)?;
+			}
+			Value::Real(n)
+		}
+	})
+}
+
+/// Parses a hexadecimal, binary or octal literal such as `0xFF`, returning None
+/// for other literals
+pub(crate) fn parse_radix_literal(literal: &str) -> Option<BigInt> {
+	let radix = match literal.get(..2)? {
+		"0x" => 16,
+		"0b" => 2,
+		"0o" => 8,
+		_ => return None,
+	};
+	BigInt::parse_bytes(&literal.as_bytes()[2..], radix)
+}
+
+/// Performs a binary operation on two values. Mixing real and decimal numbers
+/// gives a decimal result, unless the real number is infinite or NaN. Integers
+/// and rationals fall back to real numbers when mixed with them or when the
+/// result can't be represented exactly
+/// 
+/// With `strict_numerics`, division by zero and infinite or NaN results are
+/// errors
+pub(crate) fn binary_op(lhs: Value, op: Operation, rhs: Value, ctx: &Context) -> Result<Value, CalcError> {
+	// lists are checked element by element
+	if ctx.strict_numerics && !matches!((&lhs, &rhs), (Value::List(_), _) | (_, Value::List(_))) {
+		let res = unchecked_op(lhs.clone(), op, rhs.clone(), ctx)?;
+		strict::check_op(&lhs, op, &rhs, &res)?;
+		return Ok(res);
+	}
+	unchecked_op(lhs, op, rhs, ctx)
+}
+
+/// Performs a binary operation without the checks of `strict_numerics`
+fn unchecked_op(lhs: Value, op: Operation, rhs: Value, ctx: &Context) -> Result<Value, CalcError> {
+	match (lhs, rhs) {
+		(lhs @ Value::Matrix(_), rhs) | (lhs, rhs @ Value::Matrix(_)) => matrix::matrix_op(lhs, op, rhs),
+		(lhs @ Value::List(_), rhs) | (lhs, rhs @ Value::List(_)) => {
+			list::map(vec![lhs, rhs], |mut args| {
+				let rhs = args.pop().expect("Two operands");
+				binary_op(args.pop().expect("Two operands"), op, rhs, ctx)
+			})
+		}
+		(lhs, rhs) if op.is_logical() => logic_op(&lhs, op, &rhs, ctx),
+		(lhs, rhs) if ctx.number_mode == NumberMode::Programmer => fixed::fixed_op(&lhs, op, &rhs, ctx),
+		(lhs, rhs) if op.is_bitwise() => bitwise_op(&lhs, op, &rhs, ctx),
+		(lhs @ Value::Quantity(_), rhs) | (lhs, rhs @ Value::Quantity(_)) => units::quantity_op(lhs, op, rhs),
+		(Value::Real(lhs), Value::Real(rhs)) => {
+			let res = real_op(lhs, op, rhs);
+			// a negative base with a fractional exponent has a complex result
+			if ctx.number_mode == NumberMode::Complex && res.is_nan() && !lhs.is_nan() && !rhs.is_nan() {
+				return Ok(complex_op(lhs.into(), op, rhs.into()).into());
+			}
+			Ok(Value::Real(res))
+		}
+		(lhs @ Value::Complex(_), rhs) | (lhs, rhs @ Value::Complex(_)) => {
+			Ok(complex_op(lhs.to_complex(), op, rhs.to_complex()).into())
+		}
+		(Value::Integer(lhs), Value::Integer(rhs)) => match integer::integer_op(&lhs, op, &rhs) {
+			Some(res) => Ok(Value::Integer(res)),
+			None => Ok(real_fallback(&lhs.into(), op, &rhs.into())),
+		}
+		(lhs @ Value::Decimal(_), rhs) | (lhs, rhs @ Value::Decimal(_)) => match (lhs.to_decimal(), rhs.to_decimal()) {
+			(Some(lhs), Some(rhs)) => {
+				Ok(Value::Decimal(decimal::decimal_op(&lhs, op, &rhs, ctx.decimal_precision())?))
+			}
+			_ => Ok(real_fallback(&lhs, op, &rhs)),
+		}
+		(lhs, rhs) => match (lhs.to_rational(), rhs.to_rational()) {
+			(Some(lhs), Some(rhs)) => match rational::rational_op(&lhs, op, &rhs)? {
+				Some(res) => Ok(Value::Rational(res)),
+				None => Ok(real_fallback(&lhs.into(), op, &rhs.into())),
+			}
+			_ => Ok(real_fallback(&lhs, op, &rhs)),
+		}
+	}
+}
+
+/// Performs a binary operation on two values converted to real numbers
+fn real_fallback(lhs: &Value, op: Operation, rhs: &Value) -> Value {
+	let lhs = lhs.to_f64().unwrap_or(f64::NAN);
+	let rhs = rhs.to_f64().unwrap_or(f64::NAN);
+	Value::Real(real_op(lhs, op, rhs))
+}
+
+/// Performs a binary operation on two real numbers
+fn real_op(lhs: f64, op: Operation, rhs: f64) -> f64 {
+	match op {
+		Operation::Add => { lhs + rhs }
+		Operation::Sub => { lhs - rhs }
+		Operation::Mul => { lhs * rhs }
+		Operation::Div => { lhs / rhs }
+		Operation::FloorDiv => { f64::floor(lhs / rhs) }
+		Operation::Mod => { lhs % rhs }
+		Operation::Exp => { lhs.powf(rhs) }
+		Operation::Lt => { f64::from(u8::from(lhs < rhs)) }
+		Operation::Le => { f64::from(u8::from(lhs <= rhs)) }
+		Operation::Gt => { f64::from(u8::from(lhs > rhs)) }
+		Operation::Ge => { f64::from(u8::from(lhs >= rhs)) }
+		Operation::Eq => { f64::from(u8::from(lhs == rhs)) }
+		Operation::Ne => { f64::from(u8::from(lhs != rhs)) }
+		Operation::And => { f64::from(u8::from(lhs != 0.0 && rhs != 0.0)) }
+		Operation::Or => { f64::from(u8::from(lhs != 0.0 || rhs != 0.0)) }
+		_ => unreachable!("Bitwise operations are evaluated by `bitwise_op()`"),
+	}
+}
+
+/// Evaluates a comparison or logical operation on two values that aren't
+/// lists, giving 1 if it holds and 0 otherwise. Integers, rationals and
+/// decimals are compared exactly, and complex numbers can only be tested for
+/// equality
+fn logic_op(lhs: &Value, op: Operation, rhs: &Value, ctx: &Context) -> Result<Value, CalcError> {
+	let res = match op {
+		Operation::And => is_true(lhs)? && is_true(rhs)?,
+		Operation::Or => is_true(lhs)? || is_true(rhs)?,
+		Operation::Eq | Operation::Ne if matches!((lhs, rhs), (Value::Complex(_), _) | (_, Value::Complex(_))) => {
+			(lhs.to_complex() == rhs.to_complex()) == (op == Operation::Eq)
+		}
+		_ => {
+			let ordering = compare(lhs, rhs)?;
+			match op {
+				Operation::Lt => ordering == Some(Ordering::Less),
+				Operation::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+				Operation::Gt => ordering == Some(Ordering::Greater),
+				Operation::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+				Operation::Eq => ordering == Some(Ordering::Equal),
+				_ => ordering != Some(Ordering::Equal),
+			}
+		}
+	};
+	Ok(bool_value(res, ctx))
+}
+
+/// Orders two values that aren't lists, returning None if either is NaN
+fn compare(lhs: &Value, rhs: &Value) -> Result<Option<Ordering>, CalcError> {
+	match (lhs, rhs) {
+		(Value::Quantity(_), _) | (_, Value::Quantity(_)) => units::compare(lhs, rhs),
+		(Value::Complex(_), _) | (_, Value::Complex(_)) => Err(CalcError::new(CalcErrorType::CalculationError, format!("Can't order complex numbers {lhs} and {rhs}"))),
+		_ => {
+			if let (Some(lhs), Some(rhs)) = (lhs.to_rational(), rhs.to_rational()) {
+				return Ok(lhs.partial_cmp(&rhs));
+			}
+			if matches!((lhs, rhs), (Value::Decimal(_), _) | (_, Value::Decimal(_))) {
+				if let (Some(lhs), Some(rhs)) = (lhs.to_decimal(), rhs.to_decimal()) {
+					return Ok(lhs.partial_cmp(&rhs));
+				}
+			}
+			let real = |val: &Value| val.to_f64().unwrap_or(f64::NAN);
+			Ok(real(lhs).partial_cmp(&real(rhs)))
+		}
+	}
+}
+
+/// Returns true if a value that isn't a list is non-zero, used as the condition
+/// of logical operations
+pub(crate) fn is_true(val: &Value) -> Result<bool, CalcError> {
+	match val {
+		Value::Complex(z) => Ok(*z != Complex64::new(0.0, 0.0)),
+		Value::Real(_) | Value::Integer(_) | Value::Decimal(_) | Value::Rational(_) => {
+			Ok(compare(val, &Value::Integer(0.into()))? != Some(Ordering::Equal))
+		}
+		_ => Err(CalcError::new(CalcErrorType::CalculationError, format!("{val} can't be used as a condition"))),
+	}
+}
+
+/// Result of a comparison or logical operation, 1 for true and 0 for false in
+/// the kind of number used by the number mode
+pub(crate) fn bool_value(b: bool, ctx: &Context) -> Value {
+	integer_value(BigInt::from(u8::from(b)), ctx)
+}
+
+/// Turns an integer into the kind of number used by the number mode, wrapping
+/// it around in programmer mode
+pub(crate) fn integer_value(i: BigInt, ctx: &Context) -> Value {
+	match ctx.number_mode {
+		NumberMode::Decimal => Value::Decimal(BigDecimal::from(i)),
+		NumberMode::Rational => Value::Rational(BigRational::from_integer(i)),
+		NumberMode::Real if ctx.exact_integers => Value::Integer(i),
+		NumberMode::Programmer => Value::Integer(ctx.int_type.wrap(&i)),
+		_ => Value::Real(i.to_f64().unwrap_or(f64::NAN)),
+	}
+}
+
+/// Returns a value as an integer, or None if it has a fractional part or isn't
+/// a real number
+pub(crate) fn as_integer(val: &Value) -> Option<BigInt> {
+	match val {
+		Value::Integer(i) => Some(i.clone()),
+		Value::Rational(r) if r.is_integer() => Some(r.to_integer()),
+		Value::Decimal(d) if d.is_integer() => Some(d.with_scale(0).into_bigint_and_exponent().0),
+		Value::Real(n) if n.fract() == 0.0 => BigInt::from_f64(*n),
+		_ => None,
+	}
+}
+
+/// Returns a value as an integer, failing for values with a fractional part,
+/// which can't be used with bitwise operators
+fn to_integer(val: &Value) -> Result<BigInt, CalcError> {
+	as_integer(val).ok_or_else(|| CalcError::new(CalcErrorType::CalculationError, format!("Bitwise operators only take integers, not {val}")))
+}
+
+/// Performs a bitwise operation on two values that aren't lists
+fn bitwise_op(lhs: &Value, op: Operation, rhs: &Value, ctx: &Context) -> Result<Value, CalcError> {
+	let res = integer::bitwise_op(&to_integer(lhs)?, op, &to_integer(rhs)?)?;
+	Ok(integer_value(res, ctx))
+}
+
+/// Bitwise not of an integer, `-x - 1`, element-wise for lists
+pub(crate) fn bit_not(val: Value, ctx: &Context) -> Result<Value, CalcError> {
+	match val {
+		Value::List(list) => Ok(Value::List(list.into_iter().map(|item| bit_not(item, ctx)).collect::<Result<_, _>>()?)),
+		val => Ok(integer_value(!to_integer(&val)?, ctx)),
+	}
+}
+
+/// Logical not, giving 1 for zero values and 0 otherwise, element-wise for
+/// lists and matrices
+pub(crate) fn not(val: Value, ctx: &Context) -> Result<Value, CalcError> {
+	match val {
+		Value::List(_) | Value::Matrix(_) => binary_op(val, Operation::Eq, Value::Integer(0.into()), ctx),
+		val => Ok(bool_value(!is_true(&val)?, ctx)),
+	}
+}
+
+/// Negates a value, which can overflow in programmer mode
+pub(crate) fn negate(val: Value, ctx: &Context) -> Result<Value, CalcError> {
+	Ok(match val {
+		Value::List(list) => Value::List(list.into_iter().map(|item| negate(item, ctx)).collect::<Result<_, _>>()?),
+		Value::Matrix(m) => Value::Matrix(matrix::negate(m)),
+		_ if ctx.number_mode == NumberMode::Programmer => {
+			return fixed::fixed_op(&Value::Integer(0.into()), Operation::Sub, &val, ctx);
+		}
+		Value::Real(n) => Value::Real(-n),
+		Value::Integer(i) => Value::Integer(-i),
+		Value::Complex(z) => Value::Complex(-z),
+		Value::Decimal(d) => Value::Decimal(-d),
+		Value::Rational(r) => Value::Rational(-r),
+		Value::Quantity(q) => Value::Quantity(Quantity { magnitude: -q.magnitude, ..q }),
+	})
+}
+
+/// Computes the factorial of a value, extended to non-integers with the gamma
+/// function
+pub(crate) fn factorial(val: Value, ctx: &Context) -> Result<Value, CalcError> {
+	match val {
+		Value::List(list) => Ok(Value::List(list.into_iter().map(|item| factorial(item, ctx)).collect::<Result<_, _>>()?)),
+		Value::Matrix(m) => Err(CalcError::new(CalcErrorType::MatrixError, format!("Can't take the factorial of the matrix {m}"))),
+		_ if ctx.number_mode == NumberMode::Programmer => fixed::factorial(&val, ctx),
+		Value::Real(n) => real_factorial(n, ctx),
+		Value::Integer(i) => match integer::factorial(&i) {
+			Some(res) => Ok(Value::Integer(res)),
+			None => real_factorial(i.to_f64().unwrap_or(f64::NAN), ctx),
+		},
+		Value::Complex(z) => Ok(complex_gamma(z + 1.0).into()),
+		Value::Decimal(d) => Ok(Value::Decimal(decimal::factorial(&d, ctx.decimal_precision())?)),
+		Value::Rational(r) => match rational::factorial(&r) {
+			Some(res) => Ok(Value::Rational(res)),
+			None => real_factorial(r.to_f64().unwrap_or(f64::NAN), ctx),
+		},
+		Value::Quantity(q) => Err(CalcError::new(CalcErrorType::DimensionError, format!("Can't take the factorial of {q}"))),
+	}
+}
+
+/// Computes the factorial of a real number with the gamma function
+fn real_factorial(n: f64, ctx: &Context) -> Result<Value, CalcError> {
+	let res = tgamma(n + 1.0);
+	if ctx.strict_numerics {
+		strict::check_factorial(n, res)?;
+	}
+	Ok(Value::Real(res))
+}
+
+/// Converts an angle from one unit to another
+pub(crate) fn convert_angle(val: Value, from: AngleMode, to: AngleMode, ctx: &Context) -> Result<Value, CalcError> {
+	if from == to {
+		return Ok(val);
+	}
+	if let Value::Decimal(d) = &val {
+		// converting with extra digits of pi keeps results like `asin(1)` exact
+		let prec = ctx.decimal_precision();
+		let turn = |unit| match unit {
+			AngleMode::Radians => decimal::pi(prec + decimal::GUARD_DIGITS) * BigDecimal::from(2),
+			AngleMode::Degrees => BigDecimal::from(360),
+			AngleMode::Gradians => BigDecimal::from(400),
+		};
+		let res = decimal::div(&(d * turn(to)), &turn(from), prec)?;
+		return Ok(Value::Decimal(res));
+	}
+	let scaled = binary_op(val, Operation::Mul, full_turn(to, ctx)?, ctx)?;
+	binary_op(scaled, Operation::Div, full_turn(from, ctx)?, ctx)
+}
+
+/// Size of a full turn in an angle unit, computed with the precision of the
+/// number mode
+fn full_turn(unit: AngleMode, ctx: &Context) -> Result<Value, CalcError> {
+	match unit {
+		AngleMode::Radians => {
+			let pi = ctx.lookup_var(&"pi".to_string()).expect("pi is a builtin constant")?;
+			binary_op(Value::Integer(2.into()), Operation::Mul, pi, ctx)
+		}
+		AngleMode::Degrees => Ok(Value::Integer(360.into())),
+		AngleMode::Gradians => Ok(Value::Integer(400.into())),
+	}
+}
+
+/// Converts a list of values into real numbers, failing if any of them are
+/// complex, lists or matrices
+pub(crate) fn to_real_args(name: &str, args: &[Value]) -> Result<Vec<f64>, CalcError> {
+	args.iter().map(|arg| arg.to_f64().ok_or_else(|| {
+		let kind = match arg {
+			Value::List(_) => "list",
+			Value::Matrix(_) => "matrix",
+			_ => "complex",
+		};
+		CalcError::new(CalcErrorType::ArgumentError, format!("\"{name}()\" doesn't accept {kind} arguments"))
+	})).collect()
+}
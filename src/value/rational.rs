@@ -0,0 +1,129 @@
+//! Exact rational arithmetic used in rational mode
+//!
+//! Rationals are kept as fractions of big integers, so arithmetic never loses
+//! precision. Operations without an exact result, such as raising to a
+//! fractional power, return None so the caller can fall back to floats.
+
+use std::str::FromStr;
+use bigdecimal::BigDecimal;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Pow, Signed, ToPrimitive, Zero};
+
+use crate::CalcError;
+use crate::ast::Operation;
+use super::decimal::{self, division_by_zero};
+use super::integer::{self, MAX_EXACT_POWER_BITS};
+
+/// How rational results are formatted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RationalFormat {
+	/// Improper fraction, such as `3/2`
+	Fraction,
+	/// Whole number followed by a proper fraction, such as `1 1/2`
+	Mixed,
+	/// Decimal number, rounded to `precision` significant digits
+	Decimal,
+}
+
+/// Parses a number literal into an exact fraction, `0.1` becomes `1/10`
+pub(crate) fn parse_rational(literal: &str) -> BigRational {
+	let d = BigDecimal::from_str(literal).expect("Failed to parse num literal");
+	decimal_to_rational(&d)
+}
+
+/// Converts a decimal number into the equal fraction
+pub(crate) fn decimal_to_rational(d: &BigDecimal) -> BigRational {
+	let (digits, scale) = d.as_bigint_and_exponent();
+	let power = BigInt::from(10).pow(scale.unsigned_abs());
+	if scale >= 0 {
+		BigRational::new(digits, power)
+	} else {
+		BigRational::from_integer(digits * power)
+	}
+}
+
+/// Performs a binary operation on two rationals, returning None if the result
+/// isn't rational
+pub(crate) fn rational_op(lhs: &BigRational, op: Operation, rhs: &BigRational) -> Result<Option<BigRational>, CalcError> {
+	if matches!(op, Operation::Div | Operation::FloorDiv | Operation::Mod) && rhs.is_zero() {
+		return Err(division_by_zero());
+	}
+	Ok(match op {
+		Operation::Add => Some(lhs + rhs),
+		Operation::Sub => Some(lhs - rhs),
+		Operation::Mul => Some(lhs * rhs),
+		Operation::Div => Some(lhs / rhs),
+		Operation::FloorDiv => Some((lhs / rhs).floor()),
+		// like the real `%`, the remainder takes the sign of the dividend
+		Operation::Mod => Some(lhs - rhs * (lhs / rhs).trunc()),
+		Operation::Exp => return pow(lhs, rhs),
+		_ => unreachable!("Logical and bitwise operations are evaluated separately"),
+	})
+}
+
+/// Raises a rational to an integer power, returning None for fractional
+/// exponents and powers too large to compute exactly
+fn pow(base: &BigRational, exp: &BigRational) -> Result<Option<BigRational>, CalcError> {
+	if !exp.is_integer() {
+		return Ok(None);
+	}
+	let Some(n) = exp.to_integer().abs().to_u64() else {
+		return Ok(None);
+	};
+	let bits = base.numer().bits().max(base.denom().bits());
+	if bits.saturating_mul(n) > MAX_EXACT_POWER_BITS {
+		return Ok(None);
+	}
+	if exp.is_negative() && base.is_zero() {
+		return Err(division_by_zero());
+	}
+	// the bit limit keeps the exponent well within range of an i32
+	let n = if exp.is_negative() { -(n as i32) } else { n as i32 };
+	Ok(Some(base.pow(n)))
+}
+
+/// Factorial of a non-negative integer, returning None for other numbers
+pub(crate) fn factorial(x: &BigRational) -> Option<BigRational> {
+	if !x.is_integer() {
+		return None;
+	}
+	integer::factorial(&x.to_integer()).map(BigRational::from_integer)
+}
+
+/// Square root, returning None unless both the numerator and the denominator
+/// are perfect squares
+pub(crate) fn sqrt(x: &BigRational) -> Option<BigRational> {
+	if x.is_negative() {
+		return None;
+	}
+	let numer = x.numer().sqrt();
+	let denom = x.denom().sqrt();
+	if &numer * &numer != *x.numer() || &denom * &denom != *x.denom() {
+		return None;
+	}
+	Some(BigRational::new(numer, denom))
+}
+
+/// Converts a rational into a decimal number with a number of significant
+/// digits, the result is exact if the decimal expansion terminates in time
+pub(crate) fn to_decimal(x: &BigRational, prec: u64) -> BigDecimal {
+	let numer = BigDecimal::from(x.numer().clone());
+	let denom = BigDecimal::from(x.denom().clone());
+	decimal::div(&numer, &denom, prec).expect("Denominator must be non-zero")
+}
+
+/// Formats a rational as a fraction, mixed number or decimal
+pub(crate) fn format_rational(x: &BigRational, format: RationalFormat, prec: u64) -> String {
+	match format {
+		RationalFormat::Fraction => x.to_string(),
+		RationalFormat::Mixed => {
+			let whole = x.trunc();
+			if whole.is_zero() || x.is_integer() {
+				return x.to_string();
+			}
+			format!("{whole} {}", (x - &whole).abs())
+		}
+		RationalFormat::Decimal => to_decimal(x, prec).normalized().to_string(),
+	}
+}
@@ -0,0 +1,125 @@
+//! Checks of results used with `Context::strict_numerics`
+//!
+//! Real and complex arithmetic follows IEEE 754, so `1/0` is infinite and
+//! `sqrt(-1)` is NaN in real mode. With strict numerics, infinite and NaN
+//! results are reported as errors instead, naming the operation or function
+//! and the arguments that caused them. Infinite results of infinite operands,
+//! like `inf + 1`, are kept. Literals too large to represent, like `1e400`, and
+//! the `nan` constant are errors as well. Lists are checked element by element,
+//! while matrices aren't checked.
+
+use num_traits::Zero;
+
+use crate::{CalcError, CalcErrorType};
+use crate::ast::Operation;
+use super::Value;
+
+fn numeric_error(msg: String) -> CalcError {
+	CalcError::new(CalcErrorType::CalculationError, msg)
+}
+
+/// Returns true for infinite or NaN numbers and quantities, and false for
+/// everything else including lists and matrices
+fn is_non_finite(val: &Value) -> bool {
+	match val {
+		Value::Real(n) => !n.is_finite(),
+		Value::Complex(z) => !z.is_finite(),
+		Value::Quantity(q) => !q.magnitude.is_finite(),
+		_ => false,
+	}
+}
+
+fn is_zero(val: &Value) -> bool {
+	match val {
+		Value::Real(n) => *n == 0.0,
+		Value::Integer(i) => i.is_zero(),
+		Value::Complex(z) => z.is_zero(),
+		Value::Rational(r) => r.is_zero(),
+		Value::Decimal(d) => d.is_zero(),
+		Value::Quantity(q) => q.magnitude == 0.0,
+		Value::List(_) | Value::Matrix(_) => false,
+	}
+}
+
+/// Formats an operand for a message. Real numbers that are very large or very
+/// small are written with an exponent, rather than with hundreds of digits
+fn describe(val: &Value) -> String {
+	match val {
+		Value::Real(n) if n.abs() >= 1e16 || (*n != 0.0 && n.abs() < 1e-6) => format!("{n:e}"),
+		val => val.to_string(),
+	}
+}
+
+/// Checks a real number literal, failing if it's too large to represent, such
+/// as `1e400`
+pub(crate) fn check_literal(literal: &str, n: f64) -> Result<(), CalcError> {
+	if n.is_finite() {
+		return Ok(());
+	}
+	Err(numeric_error(format!("{literal} overflows to infinity")))
+}
+
+/// Checks the value of a variable, failing if it's NaN, such as `nan`
+pub(crate) fn check_var(name: &str, val: &Value) -> Result<(), CalcError> {
+	if !val.is_nan() {
+		return Ok(());
+	}
+	Err(numeric_error(format!("\"{name}\" is not a number")))
+}
+
+/// Checks the result of a binary operation, failing on division by zero, on
+/// NaN results and on infinite results of finite operands
+pub(crate) fn check_op(lhs: &Value, op: Operation, rhs: &Value, res: &Value) -> Result<(), CalcError> {
+	if !is_non_finite(res) || (!res.is_nan() && (is_non_finite(lhs) || is_non_finite(rhs))) {
+		return Ok(());
+	}
+	let divides_by_zero = match op {
+		Operation::Div | Operation::FloorDiv | Operation::Mod => is_zero(rhs),
+		// negative powers of zero divide by zero
+		Operation::Exp => is_zero(lhs),
+		_ => false,
+	};
+	let (lhs, rhs) = (describe(lhs), describe(rhs));
+	Err(numeric_error(if divides_by_zero {
+		format!("Division by zero in {lhs} {op} {rhs}")
+	} else if res.is_nan() {
+		format!("{lhs} {op} {rhs} is undefined")
+	} else {
+		format!("{lhs} {op} {rhs} overflows to infinity")
+	}))
+}
+
+/// Checks the result of a function call, failing on NaN results and on
+/// infinite results of finite arguments. Infinite results are taken to be poles
+/// when one of the arguments is zero, like `ln(0)`, and overflows otherwise
+pub(crate) fn check_call(name: &str, args: &[Value], res: &Value) -> Result<(), CalcError> {
+	if !is_non_finite(res) || (!res.is_nan() && args.iter().any(is_non_finite)) {
+		return Ok(());
+	}
+	let args_list = args.iter().map(describe).collect::<Vec<_>>().join(", ");
+	let (argument, is_are) = if args.len() == 1 { ("argument", "is") } else { ("arguments", "are") };
+	Err(numeric_error(if res.is_nan() {
+		format!("The {argument} {args_list} {is_are} outside the domain of \"{name}()\"")
+	} else if args.iter().any(is_zero) {
+		format!("\"{name}()\" is infinite for the {argument} {args_list}")
+	} else {
+		format!("\"{name}()\" overflows to infinity for the {argument} {args_list}")
+	}))
+}
+
+/// Checks the factorial of a real number computed with the gamma function,
+/// failing for negative integers, which are its poles, on NaN results and on
+/// infinite results of finite numbers
+pub(crate) fn check_factorial(n: f64, res: f64) -> Result<(), CalcError> {
+	if res.is_finite() || (!res.is_nan() && !n.is_finite()) {
+		return Ok(());
+	}
+	let n_str = describe(&Value::Real(n));
+	Err(numeric_error(if n < 0.0 && n.fract() == 0.0 {
+		format!("Can't take the factorial of the negative integer {n_str}")
+	} else if res.is_nan() {
+		format!("{n_str}! is undefined")
+	} else {
+		format!("{n_str}! overflows to infinity")
+	}))
+}
@@ -0,0 +1,295 @@
+use super::*;
+
+fn mode_ctx(mode: NumberMode) -> Context {
+	let mut ctx = Context::new();
+	ctx.number_mode = mode;
+	ctx
+}
+
+fn dec(s: &str) -> BigDecimal {
+	BigDecimal::from_str(s).unwrap()
+}
+
+#[test]
+fn test_simplify() {
+	assert_eq!(Value::Complex(Complex64::new(2.0, 0.0)).simplify(), Value::Real(2.0));
+	assert!(matches!(Value::Complex(Complex64::new(2.0, 1.0)).simplify(), Value::Complex(_)));
+}
+
+#[test]
+fn test_to_f64() {
+	assert_eq!(Value::Real(2.0).to_f64(), Some(2.0));
+	assert_eq!(Value::Complex(Complex64::new(2.0, 1.0)).to_f64(), None);
+}
+
+#[test]
+fn test_format_complex() {
+	assert_eq!(Value::Complex(Complex64::new(3.0, 4.0)).to_string(), "3+4i");
+	assert_eq!(Value::Complex(Complex64::new(3.0, -4.0)).to_string(), "3-4i");
+	assert_eq!(Value::Complex(Complex64::new(0.0, 1.0)).to_string(), "i");
+	assert_eq!(Value::Complex(Complex64::new(0.0, -2.5)).to_string(), "-2.5i");
+	assert_eq!(Value::Complex(Complex64::new(1.0, -1.0)).to_string(), "1-i");
+}
+
+#[test]
+fn test_binary_op_complex() {
+	let i = Value::Complex(Complex64::i());
+	let res = binary_op(i.clone(), Operation::Mul, i, &mode_ctx(NumberMode::Complex)).unwrap();
+	assert_eq!(res, Value::Real(-1.0));
+}
+
+#[test]
+fn test_binary_op_negative_base() {
+	let res = binary_op(Value::Real(-4.0), Operation::Exp, Value::Real(0.5), &mode_ctx(NumberMode::Real)).unwrap();
+	assert!(res.is_nan());
+	let res = binary_op(Value::Real(-4.0), Operation::Exp, Value::Real(0.5), &mode_ctx(NumberMode::Complex)).unwrap();
+	let z = res.to_complex();
+	assert!(z.re.abs() < 1e-15 && (z.im - 2.0).abs() < 1e-15);
+}
+
+#[test]
+fn test_complex_gamma() {
+	let res = complex_gamma(Complex64::new(5.0, 0.0));
+	assert!((res.re - 24.0).abs() < 1e-10 && res.im.abs() < 1e-10);
+	let res = complex_gamma(Complex64::new(0.5, 0.0));
+	assert!((res.re - std::f64::consts::PI.sqrt()).abs() < 1e-10);
+}
+
+#[test]
+fn test_to_real_args() {
+	let args = [Value::Real(1.0), Value::Complex(Complex64::i())];
+	assert!(matches!(to_real_args("f", &args).unwrap_err().error_type, CalcErrorType::ArgumentError));
+}
+
+#[test]
+fn test_to_decimal() {
+	assert_eq!(Value::Real(0.1).to_decimal(), Some(dec("0.1")));
+	assert_eq!(Value::Real(f64::INFINITY).to_decimal(), None);
+	assert_eq!(Value::Complex(Complex64::i()).to_decimal(), None);
+}
+
+#[test]
+fn test_binary_op_decimal() {
+	let ctx = mode_ctx(NumberMode::Decimal);
+	let res = binary_op(Value::Decimal(dec("0.1")), Operation::Add, Value::Real(0.2), &ctx).unwrap();
+	assert_eq!(res, Value::Decimal(dec("0.3")));
+	let res = binary_op(Value::Decimal(dec("-7")), Operation::FloorDiv, Value::Decimal(dec("2")), &ctx).unwrap();
+	assert_eq!(res, Value::Decimal(dec("-4")));
+	// like the real `%`, the remainder takes the sign of the dividend
+	let res = binary_op(Value::Decimal(dec("-7")), Operation::Mod, Value::Decimal(dec("2")), &ctx).unwrap();
+	assert_eq!(res, Value::Decimal(dec("-1")));
+	let res = binary_op(Value::Decimal(dec("1")), Operation::Div, Value::Decimal(dec("0")), &ctx);
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::CalculationError));
+	// non-finite reals can't be represented as decimals
+	let res = binary_op(Value::Decimal(dec("1")), Operation::Add, Value::Real(f64::INFINITY), &ctx).unwrap();
+	assert_eq!(res, Value::Real(f64::INFINITY));
+}
+
+#[test]
+fn test_decimal_functions() {
+	let prec = 30;
+	assert_eq!(decimal::pi(prec), dec("3.14159265358979323846264338328"));
+	assert_eq!(decimal::exp(&dec("1"), prec).unwrap(), dec("2.71828182845904523536028747135"));
+	assert_eq!(decimal::sqrt(&dec("2"), prec).unwrap(), dec("1.41421356237309504880168872421"));
+	assert_eq!(decimal::ln(&dec("10"), prec).unwrap(), dec("2.30258509299404568401799145468"));
+	assert_eq!(decimal::sin(&dec("1"), prec).unwrap(), dec("0.841470984807896506652502321630"));
+	assert_eq!(decimal::cos(&dec("1"), prec).unwrap(), dec("0.540302305868139717400936607443"));
+	assert_eq!(decimal::atan(&dec("1"), prec), dec("0.785398163397448309615660845820"));
+	assert_eq!(decimal::factorial(&dec("20"), prec).unwrap(), dec("2432902008176640000"));
+	assert_eq!(decimal::gamma(&dec("0.5"), prec).unwrap(), dec("1.77245385090551602729816748334"));
+}
+
+#[test]
+fn test_decimal_domain_errors() {
+	assert!(decimal::ln(&dec("0"), 20).is_err());
+	assert!(decimal::sqrt(&dec("-1"), 20).is_err());
+	assert!(decimal::asin(&dec("2"), 20).is_err());
+	assert!(decimal::factorial(&dec("-1"), 20).is_err());
+}
+
+fn rat(numer: i64, denom: i64) -> BigRational {
+	BigRational::new(numer.into(), denom.into())
+}
+
+#[test]
+fn test_parse_rational() {
+	assert_eq!(rational::parse_rational("0.25"), rat(1, 4));
+	assert_eq!(rational::parse_rational("1.5e3"), rat(1500, 1));
+	assert_eq!(rational::parse_rational("2e-2"), rat(1, 50));
+}
+
+#[test]
+fn test_binary_op_rational() {
+	let ctx = mode_ctx(NumberMode::Rational);
+	let res = binary_op(Value::Rational(rat(1, 3)), Operation::Add, Value::Rational(rat(1, 6)), &ctx).unwrap();
+	assert_eq!(res, Value::Rational(rat(1, 2)));
+	let res = binary_op(Value::Rational(rat(-7, 2)), Operation::FloorDiv, Value::Rational(rat(1, 1)), &ctx).unwrap();
+	assert_eq!(res, Value::Rational(rat(-4, 1)));
+	let res = binary_op(Value::Rational(rat(-7, 2)), Operation::Mod, Value::Rational(rat(1, 1)), &ctx).unwrap();
+	assert_eq!(res, Value::Rational(rat(-1, 2)));
+	let res = binary_op(Value::Rational(rat(2, 3)), Operation::Exp, Value::Rational(rat(-2, 1)), &ctx).unwrap();
+	assert_eq!(res, Value::Rational(rat(9, 4)));
+	// fractional exponents and real operands fall back to floats
+	let res = binary_op(Value::Rational(rat(4, 1)), Operation::Exp, Value::Rational(rat(1, 2)), &ctx).unwrap();
+	assert_eq!(res, Value::Real(2.0));
+	let res = binary_op(Value::Rational(rat(1, 2)), Operation::Add, Value::Real(0.25), &ctx).unwrap();
+	assert_eq!(res, Value::Real(0.75));
+	let res = binary_op(Value::Rational(rat(1, 1)), Operation::Div, Value::Rational(rat(0, 1)), &ctx);
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::CalculationError));
+}
+
+#[test]
+fn test_format_rational() {
+	let prec = 10;
+	assert_eq!(rational::format_rational(&rat(7, 2), RationalFormat::Fraction, prec), "7/2");
+	assert_eq!(rational::format_rational(&rat(7, 2), RationalFormat::Mixed, prec), "3 1/2");
+	assert_eq!(rational::format_rational(&rat(-7, 2), RationalFormat::Mixed, prec), "-3 1/2");
+	assert_eq!(rational::format_rational(&rat(-1, 2), RationalFormat::Mixed, prec), "-1/2");
+	assert_eq!(rational::format_rational(&rat(4, 1), RationalFormat::Mixed, prec), "4");
+	assert_eq!(rational::format_rational(&rat(7, 8), RationalFormat::Decimal, prec), "0.875");
+	assert_eq!(rational::format_rational(&rat(2, 3), RationalFormat::Decimal, prec), "0.6666666667");
+}
+
+#[test]
+fn test_rational_sqrt() {
+	assert_eq!(rational::sqrt(&rat(9, 4)), Some(rat(3, 2)));
+	assert_eq!(rational::sqrt(&rat(2, 1)), None);
+	assert_eq!(rational::sqrt(&rat(-4, 1)), None);
+}
+
+#[test]
+fn test_integer_op() {
+	let int = |n: i64| BigInt::from(n);
+	assert_eq!(integer::integer_op(&int(-7), Operation::FloorDiv, &int(2)), Some(int(-4)));
+	assert_eq!(integer::integer_op(&int(-7), Operation::Mod, &int(2)), Some(int(-1)));
+	assert_eq!(integer::integer_op(&int(12), Operation::Div, &int(4)), Some(int(3)));
+	assert_eq!(integer::integer_op(&int(7), Operation::Div, &int(2)), None);
+	assert_eq!(integer::integer_op(&int(7), Operation::Mod, &int(0)), None);
+	assert_eq!(integer::integer_op(&int(2), Operation::Exp, &int(-1)), None);
+	assert_eq!(integer::integer_op(&int(2), Operation::Exp, &int(10_000_000)), None);
+	assert_eq!(integer::integer_op(&int(2), Operation::Exp, &int(64)), Some(BigInt::from(u64::MAX) + 1));
+}
+
+#[test]
+fn test_binary_op_integer() {
+	let ctx = mode_ctx(NumberMode::Real);
+	let res = binary_op(Value::Integer(7.into()), Operation::Div, Value::Integer(2.into()), &ctx).unwrap();
+	assert_eq!(res, Value::Real(3.5));
+	let res = binary_op(Value::Integer(3.into()), Operation::Mul, Value::Real(0.5), &ctx).unwrap();
+	assert_eq!(res, Value::Real(1.5));
+	let res = binary_op(Value::Integer(1.into()), Operation::Add, Value::Rational(rat(1, 2)), &ctx).unwrap();
+	assert_eq!(res, Value::Rational(rat(3, 2)));
+}
+
+#[test]
+fn test_lookup_unit() {
+	let km = units::lookup_unit("km").unwrap();
+	assert_eq!((km.magnitude, km.dimension), (1000.0, Dimension([0, 1, 0, 0, 0, 0, 0])));
+	assert_eq!(units::lookup_unit("kg").unwrap().magnitude, 1.0);
+	assert_eq!(units::lookup_unit("µs").unwrap().magnitude, 1e-6);
+	// exact symbols take priority over prefixes
+	assert_eq!(units::lookup_unit("min").unwrap().magnitude, 60.0);
+	assert_eq!(units::lookup_unit("mi").unwrap().magnitude, 1609.344);
+	// only metric units accept prefixes
+	assert!(units::lookup_unit("kmph").is_none());
+	assert!(units::lookup_unit("x").is_none());
+}
+
+#[test]
+fn test_format_dimension() {
+	assert_eq!(Dimension([0, 1, -1, 0, 0, 0, 0]).to_string(), "m/s");
+	assert_eq!(Dimension([1, 1, -2, 0, 0, 0, 0]).to_string(), "N");
+	assert_eq!(Dimension([1, 0, -1, 0, 0, 0, 0]).to_string(), "kg/s");
+	assert_eq!(Dimension([0, 2, 0, 0, -1, -1, 0]).to_string(), "m^2/(K*mol)");
+	assert_eq!(Dimension([0, 0, -2, 0, 0, 0, 0]).to_string(), "s^-2");
+}
+
+#[test]
+fn test_quantity_op() {
+	let metre = || Value::Quantity(units::lookup_unit("m").unwrap());
+	let second = || Value::Quantity(units::lookup_unit("s").unwrap());
+	let res = units::quantity_op(metre(), Operation::Div, metre()).unwrap();
+	assert_eq!(res, Value::Real(1.0));
+	let res = units::quantity_op(metre(), Operation::Add, second());
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::DimensionError));
+	let res = units::quantity_op(metre(), Operation::Exp, Value::Real(0.5));
+	assert!(matches!(res.unwrap_err().error_type, CalcErrorType::DimensionError));
+}
+
+#[test]
+fn test_list_broadcast() {
+	let ints = |v: &[i64]| Value::List(v.iter().map(|&i| Value::Integer(i.into())).collect());
+	let pairs = list::broadcast(vec![ints(&[1, 2]), Value::Real(3.0)]).unwrap();
+	assert_eq!(pairs, vec![
+		vec![Value::Integer(1.into()), Value::Real(3.0)],
+		vec![Value::Integer(2.into()), Value::Real(3.0)],
+	]);
+	assert!(list::broadcast(vec![ints(&[1, 2]), ints(&[1])]).is_err());
+	assert_eq!(list::flatten(vec![ints(&[1]), Value::List(vec![ints(&[2, 3])])]), vec![
+		Value::Integer(1.into()), Value::Integer(2.into()), Value::Integer(3.into()),
+	]);
+}
+
+#[test]
+fn test_list_slice() {
+	let list = || Value::List((0..5).map(|i| Value::Integer(i.into())).collect());
+	let int = |i: i64| Value::Integer(i.into());
+	assert_eq!(list::index(list(), &int(-2)).unwrap(), int(3));
+	assert_eq!(list::slice(list(), Some(&int(1)), Some(&int(-1))).unwrap(), Value::List(vec![int(1), int(2), int(3)]));
+	// bounds outside of the list are clamped
+	assert_eq!(list::slice(list(), Some(&int(4)), Some(&int(2))).unwrap(), Value::List(vec![]));
+	assert_eq!(list::slice(list(), Some(&int(-9)), None).unwrap(), list());
+}
+
+#[test]
+fn test_matrix_op() {
+	let m = |rows: Vec<Vec<f64>>| Value::Matrix(Matrix::from_rows(rows).unwrap());
+	let a = || m(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+	let res = matrix::matrix_op(a(), Operation::Mul, m(vec![vec![1.0], vec![1.0]])).unwrap();
+	assert_eq!(res, m(vec![vec![3.0], vec![7.0]]));
+	let res = matrix::matrix_op(Value::Integer(1.into()), Operation::Sub, a()).unwrap();
+	assert_eq!(res, m(vec![vec![0.0, -1.0], vec![-2.0, -3.0]]));
+	let res = matrix::matrix_op(a(), Operation::Exp, Value::Integer((-1).into())).unwrap();
+	assert_eq!(res, m(vec![vec![-2.0, 1.0], vec![1.5, -0.5]]));
+	assert!(matches!(matrix::matrix_op(a(), Operation::Exp, Value::Real(0.5)).unwrap_err().error_type, CalcErrorType::MatrixError));
+	assert!(matches!(matrix::matrix_op(Value::Real(1.0), Operation::Div, a()).unwrap_err().error_type, CalcErrorType::MatrixError));
+}
+
+#[test]
+fn test_matrix_from_list() {
+	let ints = |v: &[i64]| Value::List(v.iter().map(|&i| Value::Integer(i.into())).collect());
+	let res = matrix::from_list(vec![ints(&[1, 2]), ints(&[3, 4])]).unwrap();
+	assert_eq!(res, Value::Matrix(Matrix::from_rows(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap()));
+	// lists mixing numbers and lists aren't matrices
+	assert!(matches!(matrix::from_list(vec![ints(&[1, 2]), Value::Real(3.0)]).unwrap(), Value::List(_)));
+	assert!(matrix::from_list(vec![ints(&[1, 2]), ints(&[3])]).is_err());
+	assert!(matrix::from_list(vec![ints(&[]), ints(&[])]).is_err());
+}
+
+#[test]
+fn test_logic_op() {
+	let ctx = Context::new();
+	let int = |i: i64| Value::Integer(i.into());
+	let res = binary_op(Value::Real(0.5), Operation::Lt, Value::Rational(rat(2, 3)), &ctx).unwrap();
+	assert_eq!(res, int(1));
+	let res = binary_op(Value::Decimal(dec("0.1")), Operation::Eq, Value::Rational(rat(1, 10)), &ctx).unwrap();
+	assert_eq!(res, int(1));
+	// NaN is unordered, and only unequal to everything
+	assert_eq!(binary_op(Value::Real(f64::NAN), Operation::Ge, int(0), &ctx).unwrap(), int(0));
+	assert_eq!(binary_op(Value::Real(f64::NAN), Operation::Ne, Value::Real(f64::NAN), &ctx).unwrap(), int(1));
+	let ctx = mode_ctx(NumberMode::Decimal);
+	let res = binary_op(Value::Decimal(dec("2")), Operation::Or, Value::Decimal(dec("0")), &ctx).unwrap();
+	assert_eq!(res, Value::Decimal(dec("1")));
+}
+
+#[test]
+fn test_bitwise_op() {
+	let int = |i: i64| BigInt::from(i);
+	assert_eq!(integer::bitwise_op(&int(-1), Operation::BitAnd, &int(0xFF)).unwrap(), int(0xFF));
+	assert_eq!(integer::bitwise_op(&int(-8), Operation::BitXor, &int(3)).unwrap(), int(-5));
+	assert_eq!(integer::bitwise_op(&int(-5), Operation::Shr, &int(1)).unwrap(), int(-3));
+	let huge = int(2).pow(80);
+	assert_eq!(integer::bitwise_op(&int(-5), Operation::Shr, &huge).unwrap(), int(-1));
+	assert_eq!(integer::bitwise_op(&int(0), Operation::Shl, &huge).unwrap(), int(0));
+	assert!(integer::bitwise_op(&int(1), Operation::Shl, &huge).is_err());
+}
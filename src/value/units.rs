@@ -0,0 +1,349 @@
+//! Physical units and dimensional analysis
+//!
+//! Quantities are stored as a magnitude in SI base units along with the
+//! exponents of the seven base dimensions. Identifiers that aren't variables
+//! are looked up as units, optionally with an SI prefix, so `5 km` is the
+//! quantity 5000 m.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::{CalcError, CalcErrorType};
+use crate::ast::{Expr, ExprKind, Operation};
+use super::Value;
+
+/// Symbols of the SI base units, in the order of the dimension exponents
+const BASE_UNITS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+/// Exponents of mass, length, time, current, temperature, amount of substance
+/// and luminous intensity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dimension(pub [i8; 7]);
+
+impl Dimension {
+	/// Dimension of a plain number
+	pub const NONE: Self = Self([0; 7]);
+
+	/// Returns true if the dimension is that of a plain number
+	pub fn is_none(&self) -> bool {
+		*self == Self::NONE
+	}
+
+	fn combine(self, other: Self, f: impl Fn(i8, i8) -> i8) -> Self {
+		let mut res = self.0;
+		for (a, b) in res.iter_mut().zip(other.0) {
+			*a = f(*a, b);
+		}
+		Self(res)
+	}
+
+	/// Dimension raised to a power, or None if an exponent isn't an integer
+	fn pow(self, n: f64) -> Option<Self> {
+		let mut res = [0; 7];
+		for (r, d) in res.iter_mut().zip(self.0) {
+			let exp = d as f64 * n;
+			if exp.fract() != 0.0 || exp.abs() > i8::MAX as f64 {
+				return None;
+			}
+			*r = exp as i8;
+		}
+		Some(Self(res))
+	}
+}
+
+impl fmt::Display for Dimension {
+	/// Formats the dimension as a named derived unit such as `N`, or as a
+	/// combination of base units such as `m/s^2`
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		if let Some(unit) = DERIVED_NAMES.iter().find(|name| lookup_unit(name).is_some_and(|q| q.dimension == *self)) {
+			return write!(formatter, "{unit}");
+		}
+		let power = |unit: &str, exp: i8| if exp == 1 { unit.to_string() } else { format!("{unit}^{exp}") };
+		let numerator: Vec<String> = BASE_UNITS.iter().zip(self.0)
+			.filter(|(_, exp)| *exp > 0)
+			.map(|(unit, exp)| power(unit, exp))
+			.collect();
+		// without a numerator, units are written with negative exponents
+		if numerator.is_empty() {
+			let units: Vec<String> = BASE_UNITS.iter().zip(self.0)
+				.filter(|(_, exp)| *exp < 0)
+				.map(|(unit, exp)| power(unit, exp))
+				.collect();
+			return write!(formatter, "{}", units.join("*"));
+		}
+		let denominator: Vec<String> = BASE_UNITS.iter().zip(self.0)
+			.filter(|(_, exp)| *exp < 0)
+			.map(|(unit, exp)| power(unit, -exp))
+			.collect();
+		write!(formatter, "{}", numerator.join("*"))?;
+		match denominator.len() {
+			0 => Ok(()),
+			1 => write!(formatter, "/{}", denominator[0]),
+			_ => write!(formatter, "/({})", denominator.join("*")),
+		}
+	}
+}
+
+/// A number with a physical unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+	/// Magnitude in SI base units
+	pub magnitude: f64,
+	/// Physical dimension of the quantity
+	pub dimension: Dimension,
+	/// Unit the quantity was converted to with `to`, and its size in SI base
+	/// units
+	pub(crate) unit: Option<(String, f64)>,
+}
+
+impl Quantity {
+	fn new(magnitude: f64, dimension: Dimension) -> Self {
+		Self { magnitude, dimension, unit: None }
+	}
+
+	/// Magnitude in the unit the quantity is displayed in
+	pub fn value(&self) -> f64 {
+		match &self.unit {
+			Some((_, size)) => self.magnitude / size,
+			None => self.magnitude,
+		}
+	}
+
+	/// Name of the unit the quantity is displayed in
+	pub fn unit_name(&self) -> String {
+		match &self.unit {
+			Some((name, _)) => name.clone(),
+			None => self.dimension.to_string(),
+		}
+	}
+
+	/// Name of the unit used in error messages, which calls plain numbers
+	/// dimensionless rather than leaving the unit out
+	fn unit_description(&self) -> String {
+		let name = self.unit_name();
+		if name.is_empty() { "dimensionless".to_string() } else { name }
+	}
+
+	/// Turns the quantity into a plain number if it has no dimension
+	fn simplify(self) -> Value {
+		if self.dimension.is_none() && self.unit.is_none() {
+			Value::Real(self.magnitude)
+		} else {
+			Value::Quantity(self)
+		}
+	}
+}
+
+impl fmt::Display for Quantity {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "{} {}", self.value(), self.unit_name())
+	}
+}
+
+/// Unit that can be written in expressions
+struct Unit {
+	symbol: &'static str,
+	/// Size of the unit in SI base units
+	size: f64,
+	dimension: [i8; 7],
+	/// Whether the unit accepts SI prefixes such as `k` or `m`
+	prefixable: bool,
+}
+
+const fn unit(symbol: &'static str, size: f64, dimension: [i8; 7], prefixable: bool) -> Unit {
+	Unit { symbol, size, dimension, prefixable }
+}
+
+const UNITS: &[Unit] = &[
+	// base units, with the gram standing in for the kilogram
+	unit("m", 1.0, [0, 1, 0, 0, 0, 0, 0], true),
+	unit("g", 1e-3, [1, 0, 0, 0, 0, 0, 0], true),
+	unit("s", 1.0, [0, 0, 1, 0, 0, 0, 0], true),
+	unit("A", 1.0, [0, 0, 0, 1, 0, 0, 0], true),
+	unit("K", 1.0, [0, 0, 0, 0, 1, 0, 0], true),
+	unit("mol", 1.0, [0, 0, 0, 0, 0, 1, 0], true),
+	unit("cd", 1.0, [0, 0, 0, 0, 0, 0, 1], true),
+	// derived units
+	unit("Hz", 1.0, [0, 0, -1, 0, 0, 0, 0], true),
+	unit("N", 1.0, [1, 1, -2, 0, 0, 0, 0], true),
+	unit("Pa", 1.0, [1, -1, -2, 0, 0, 0, 0], true),
+	unit("J", 1.0, [1, 2, -2, 0, 0, 0, 0], true),
+	unit("W", 1.0, [1, 2, -3, 0, 0, 0, 0], true),
+	unit("C", 1.0, [0, 0, 1, 1, 0, 0, 0], true),
+	unit("V", 1.0, [1, 2, -3, -1, 0, 0, 0], true),
+	unit("Ω", 1.0, [1, 2, -3, -2, 0, 0, 0], true),
+	unit("ohm", 1.0, [1, 2, -3, -2, 0, 0, 0], true),
+	unit("F", 1.0, [-1, -2, 4, 2, 0, 0, 0], true),
+	unit("H", 1.0, [1, 2, -2, -2, 0, 0, 0], true),
+	unit("T", 1.0, [1, 0, -2, -1, 0, 0, 0], true),
+	unit("Wb", 1.0, [1, 2, -2, -1, 0, 0, 0], true),
+	// other metric units
+	unit("L", 1e-3, [0, 3, 0, 0, 0, 0, 0], true),
+	unit("t", 1e3, [1, 0, 0, 0, 0, 0, 0], false),
+	unit("bar", 1e5, [1, -1, -2, 0, 0, 0, 0], true),
+	unit("eV", 1.602_176_634e-19, [1, 2, -2, 0, 0, 0, 0], true),
+	unit("Wh", 3600.0, [1, 2, -2, 0, 0, 0, 0], true),
+	unit("cal", 4.184, [1, 2, -2, 0, 0, 0, 0], true),
+	unit("min", 60.0, [0, 0, 1, 0, 0, 0, 0], false),
+	unit("h", 3600.0, [0, 0, 1, 0, 0, 0, 0], false),
+	unit("d", 86400.0, [0, 0, 1, 0, 0, 0, 0], false),
+	unit("atm", 101_325.0, [1, -1, -2, 0, 0, 0, 0], false),
+	// imperial and US customary units
+	unit("in", 0.0254, [0, 1, 0, 0, 0, 0, 0], false),
+	unit("ft", 0.3048, [0, 1, 0, 0, 0, 0, 0], false),
+	unit("yd", 0.9144, [0, 1, 0, 0, 0, 0, 0], false),
+	unit("mi", 1609.344, [0, 1, 0, 0, 0, 0, 0], false),
+	unit("mph", 0.447_04, [0, 1, -1, 0, 0, 0, 0], false),
+	unit("lb", 0.453_592_37, [1, 0, 0, 0, 0, 0, 0], false),
+	unit("oz", 0.028_349_523_125, [1, 0, 0, 0, 0, 0, 0], false),
+	unit("gal", 3.785_411_784e-3, [0, 3, 0, 0, 0, 0, 0], false),
+	unit("hp", 745.699_871_582_270_2, [1, 2, -3, 0, 0, 0, 0], false),
+];
+
+/// Derived units used to display results of matching dimensions
+const DERIVED_NAMES: [&str; 9] = ["N", "Pa", "J", "W", "C", "V", "Ω", "F", "H"];
+
+const PREFIXES: [(&str, f64); 22] = [
+	("Y", 1e24), ("Z", 1e21), ("E", 1e18), ("P", 1e15), ("T", 1e12), ("G", 1e9),
+	("M", 1e6), ("k", 1e3), ("h", 1e2), ("da", 1e1), ("d", 1e-1), ("c", 1e-2),
+	("m", 1e-3), ("µ", 1e-6), ("μ", 1e-6), ("u", 1e-6), ("n", 1e-9), ("p", 1e-12),
+	("f", 1e-15), ("a", 1e-18), ("z", 1e-21), ("y", 1e-24),
+];
+
+/// Looks up a unit by its symbol, which may start with an SI prefix
+pub(crate) fn lookup_unit(name: &str) -> Option<Quantity> {
+	let find = |symbol: &str| UNITS.iter().find(|u| u.symbol == symbol);
+	if let Some(u) = find(name) {
+		return Some(Quantity::new(u.size, Dimension(u.dimension)));
+	}
+	PREFIXES.iter().find_map(|(prefix, factor)| {
+		let u = find(name.strip_prefix(prefix)?).filter(|u| u.prefixable)?;
+		Some(Quantity::new(factor * u.size, Dimension(u.dimension)))
+	})
+}
+
+fn dimension_error(msg: String) -> CalcError {
+	CalcError::new(CalcErrorType::DimensionError, msg)
+}
+
+/// Turns a value into a quantity, plain numbers being dimensionless
+fn to_quantity(val: Value) -> Result<Quantity, CalcError> {
+	match val {
+		Value::Quantity(q) => Ok(q),
+		val => match val.to_f64() {
+			Some(n) => Ok(Quantity::new(n, Dimension::NONE)),
+			None => Err(CalcError::new(CalcErrorType::CalculationError, format!("{val} can't have a unit"))),
+		}
+	}
+}
+
+/// Performs a binary operation where at least one side has a unit
+pub(crate) fn quantity_op(lhs: Value, op: Operation, rhs: Value) -> Result<Value, CalcError> {
+	let lhs = to_quantity(lhs)?;
+	let rhs = to_quantity(rhs)?;
+	let mismatch = || dimension_error(format!("Incompatible units in {} {op} {}", lhs.unit_description(), rhs.unit_description()));
+	let res = match op {
+		Operation::Add | Operation::Sub | Operation::Mod => {
+			if lhs.dimension != rhs.dimension {
+				return Err(mismatch());
+			}
+			let magnitude = match op {
+				Operation::Add => lhs.magnitude + rhs.magnitude,
+				Operation::Sub => lhs.magnitude - rhs.magnitude,
+				_ => lhs.magnitude % rhs.magnitude,
+			};
+			// the result keeps the unit the left-hand side was converted to
+			Quantity { magnitude, dimension: lhs.dimension, unit: lhs.unit.clone() }
+		}
+		Operation::FloorDiv => {
+			if lhs.dimension != rhs.dimension {
+				return Err(mismatch());
+			}
+			Quantity::new(f64::floor(lhs.magnitude / rhs.magnitude), Dimension::NONE)
+		}
+		Operation::Mul => {
+			let dimension = lhs.dimension.combine(rhs.dimension, |a, b| a + b);
+			let unit = if rhs.dimension.is_none() { lhs.unit.clone() } else if lhs.dimension.is_none() { rhs.unit.clone() } else { None };
+			Quantity { magnitude: lhs.magnitude * rhs.magnitude, dimension, unit }
+		}
+		Operation::Div => {
+			let dimension = lhs.dimension.combine(rhs.dimension, |a, b| a - b);
+			let unit = if rhs.dimension.is_none() { lhs.unit.clone() } else { None };
+			Quantity { magnitude: lhs.magnitude / rhs.magnitude, dimension, unit }
+		}
+		Operation::Exp => {
+			if !rhs.dimension.is_none() {
+				return Err(dimension_error(format!("Exponent {rhs} must not have a unit")));
+			}
+			let Some(dimension) = lhs.dimension.pow(rhs.magnitude) else {
+				return Err(dimension_error(format!("Raising {} to the power of {} gives a fractional dimension", lhs.unit_description(), rhs.magnitude)));
+			};
+			Quantity::new(lhs.magnitude.powf(rhs.magnitude), dimension)
+		}
+		_ => unreachable!("Logical and bitwise operations are evaluated separately"),
+	};
+	Ok(res.simplify())
+}
+
+/// Compares two values where at least one side has a unit, returning None if
+/// either magnitude is NaN
+pub(crate) fn compare(lhs: &Value, rhs: &Value) -> Result<Option<Ordering>, CalcError> {
+	let lhs = to_quantity(lhs.clone())?;
+	let rhs = to_quantity(rhs.clone())?;
+	if lhs.dimension != rhs.dimension {
+		return Err(dimension_error(format!("Can't compare {} with {}", lhs.unit_description(), rhs.unit_description())));
+	}
+	Ok(lhs.magnitude.partial_cmp(&rhs.magnitude))
+}
+
+/// Converts a quantity into the unit given by another quantity, such as with
+/// `60 mph to m/s`
+pub(crate) fn convert(val: Value, target: Value, unit_name: String) -> Result<Value, CalcError> {
+	if let Value::List(list) = val {
+		let res = list.into_iter().map(|item| convert(item, target.clone(), unit_name.clone()));
+		return Ok(Value::List(res.collect::<Result<_, _>>()?));
+	}
+	let val = to_quantity(val)?;
+	let target = to_quantity(target)?;
+	if val.dimension != target.dimension {
+		return Err(dimension_error(format!("Can't convert {} to {unit_name}", val.unit_description())));
+	}
+	if target.magnitude == 0.0 || !target.magnitude.is_finite() {
+		return Err(dimension_error(format!("Can't convert to {unit_name}, its size is {}", target.magnitude)));
+	}
+	Ok(Value::Quantity(Quantity { magnitude: val.magnitude, dimension: val.dimension, unit: Some((unit_name, target.magnitude)) }))
+}
+
+/// Calls a builtin function with quantities as arguments, only the functions
+/// that preserve units are supported
+pub(crate) fn call_function(name: &str, args: Vec<Value>) -> Result<Value, CalcError> {
+	let args: Vec<Quantity> = args.into_iter().map(to_quantity).collect::<Result<_, _>>()?;
+	match name {
+		"abs" => Ok(Value::Quantity(Quantity { magnitude: args[0].magnitude.abs(), ..args[0].clone() })),
+		"sqrt" => quantity_op(Value::Quantity(args[0].clone()), Operation::Exp, Value::Real(0.5)),
+		"min" | "max" => {
+			if args[0].dimension != args[1].dimension {
+				return Err(dimension_error(format!("Incompatible units {} and {} in \"{name}()\"", args[0].unit_description(), args[1].unit_description())));
+			}
+			let smaller = args[0].magnitude <= args[1].magnitude;
+			let pick = if smaller == (name == "min") { &args[0] } else { &args[1] };
+			Ok(pick.clone().simplify())
+		}
+		_ => Err(dimension_error(format!("\"{name}()\" doesn't accept arguments with units"))),
+	}
+}
+
+/// Formats an expression made of units, such as the target of a conversion
+pub(crate) fn format_unit_expr(expr: &Expr) -> String {
+	match &expr.kind {
+		ExprKind::Op(lhs, op, rhs) => {
+			let rhs_text = format_unit_expr(rhs);
+			let rhs_text = match (op, &rhs.kind) {
+				(Operation::Div, ExprKind::Op(_, Operation::Mul | Operation::Div, _)) => format!("({rhs_text})"),
+				_ => rhs_text,
+			};
+			format!("{}{op}{rhs_text}", format_unit_expr(lhs))
+		}
+		_ => expr.to_string(),
+	}
+}
@@ -1,6 +1,6 @@
 //! Integration tests
 
-use sci_calc::{calculate, context::Context};
+use sci_calc::{calculate, calculate_script, execute, Answer, CalcError, CalcErrorType, Span, context::{Context, Arity, NumberMode, AngleMode, IntType, OverflowMode}, value::{Value, Complex64, RationalFormat}};
 
 #[test]
 fn test_simple_1() {
@@ -118,4 +118,1234 @@ fn test_pythagorean() {
 	let _ = calculate("a = 2", &mut ctx);
 	let _ = calculate("b = 5", &mut ctx);
 	assert_eq!(calculate("sqrt(a^2 + b^2)", &mut ctx).unwrap(), (29f64).powf(0.5));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_unary_minus_1() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("-sqrt(4)", &mut ctx).unwrap(), -2.0);
+}
+
+#[test]
+fn test_unary_minus_2() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("2*-pi", &mut ctx).unwrap(), -2.0 * std::f64::consts::PI);
+}
+
+#[test]
+fn test_unary_minus_3() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("--3", &mut ctx).unwrap(), 3.0);
+}
+
+#[test]
+fn test_unary_minus_4() {
+	let mut ctx = Context::new();
+	let _ = calculate("x = 5", &mut ctx);
+	assert_eq!(calculate("-x + 1", &mut ctx).unwrap(), -4.0);
+}
+
+#[test]
+fn test_unary_minus_precedence() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("-2^2", &mut ctx).unwrap(), -4.0);
+}
+
+#[test]
+fn test_unary_plus() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("+5 - +3", &mut ctx).unwrap(), 2.0);
+}
+
+#[test]
+fn test_subtraction_no_spaces() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("5-3", &mut ctx).unwrap(), 2.0);
+	assert_eq!(calculate("5-(3)", &mut ctx).unwrap(), 2.0);
+}
+
+#[test]
+fn test_exponent_right_assoc() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("2^3^2", &mut ctx).unwrap(), 512.0);
+}
+
+#[test]
+fn test_exponent_negative() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("2^-1", &mut ctx).unwrap(), 0.5);
+	assert_eq!(calculate("2^-2^2", &mut ctx).unwrap(), 0.0625);
+}
+
+#[test]
+fn test_precedence_factorial_exponent() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("2^3!", &mut ctx).unwrap(), 64.0);
+	assert_eq!(calculate("3!^2", &mut ctx).unwrap(), 36.0);
+}
+
+#[test]
+fn test_precedence_factorial_unary() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("-3!", &mut ctx).unwrap(), -6.0);
+}
+
+#[test]
+fn test_precedence_unary_multiplicative() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("-6 / -2 * 3", &mut ctx).unwrap(), 9.0);
+	assert_eq!(calculate("-7 % 4", &mut ctx).unwrap(), -3.0);
+}
+
+#[test]
+fn test_precedence_left_assoc() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("10 - 4 - 3", &mut ctx).unwrap(), 3.0);
+	assert_eq!(calculate("64 / 4 / 2", &mut ctx).unwrap(), 8.0);
+	assert_eq!(calculate("7 // 2 * 2", &mut ctx).unwrap(), 6.0);
+}
+
+#[test]
+fn test_implicit_mul_1() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("2pi", &mut ctx).unwrap(), 2.0 * std::f64::consts::PI);
+}
+
+#[test]
+fn test_implicit_mul_2() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("3(4+5)", &mut ctx).unwrap(), 27.0);
+}
+
+#[test]
+fn test_implicit_mul_3() {
+	let mut ctx = Context::new();
+	let _ = calculate("a = 3", &mut ctx);
+	assert_eq!(calculate("(a+1)(a-1)", &mut ctx).unwrap(), 8.0);
+	assert_eq!(calculate("a(2)", &mut ctx).unwrap(), 6.0);
+}
+
+#[test]
+fn test_implicit_mul_4() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("2 sqrt(4)", &mut ctx).unwrap(), 4.0);
+	assert_eq!(calculate("2e", &mut ctx).unwrap(), 2.0 * std::f64::consts::E);
+	assert_eq!(calculate("2e2", &mut ctx).unwrap(), 200.0);
+}
+
+#[test]
+fn test_implicit_mul_precedence() {
+	let mut ctx = Context::new();
+	let _ = calculate("x = 3", &mut ctx);
+	assert_eq!(calculate("2x^2", &mut ctx).unwrap(), 18.0);
+	assert_eq!(calculate("2 -x", &mut ctx).unwrap(), -1.0);
+	// implicit multiplication binds tighter than `*` and `/`
+	assert_eq!(calculate("6 / 2x", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("6 / 2 * x", &mut ctx).unwrap(), 9.0);
+}
+
+#[test]
+fn test_implicit_mul_disabled() {
+	let mut ctx = Context::new();
+	ctx.implicit_mul = false;
+	assert!(calculate("2pi", &mut ctx).is_err());
+	assert!(calculate("3(4+5)", &mut ctx).is_err());
+	let _ = calculate("a = 3", &mut ctx);
+	assert!(calculate("a(2)", &mut ctx).is_err());
+}
+
+#[test]
+fn test_user_function_1() {
+	let mut ctx = Context::new();
+	assert!(execute("f(x, y) = x^2 + y", &mut ctx).is_ok());
+	assert_eq!(calculate("f(3, 4)", &mut ctx).unwrap(), 13.0);
+}
+
+#[test]
+fn test_user_function_2() {
+	let mut ctx = Context::new();
+	execute("sq(x) = x * x", &mut ctx).unwrap();
+	execute("hyp(a, b) = sqrt(sq(a) + sq(b))", &mut ctx).unwrap();
+	assert_eq!(calculate("hyp(3, 4)", &mut ctx).unwrap(), 5.0);
+}
+
+#[test]
+fn test_user_function_scope() {
+	let mut ctx = Context::new();
+	let _ = calculate("x = 10", &mut ctx);
+	let _ = calculate("k = 2", &mut ctx);
+	execute("f(x) = k * x", &mut ctx).unwrap();
+	assert_eq!(calculate("f(3)", &mut ctx).unwrap(), 6.0);
+	assert_eq!(calculate("x", &mut ctx).unwrap(), 10.0);
+}
+
+#[test]
+fn test_user_function_redefine() {
+	let mut ctx = Context::new();
+	execute("f(x) = x + 1", &mut ctx).unwrap();
+	execute("f(x) = x + 2", &mut ctx).unwrap();
+	assert_eq!(calculate("f(1)", &mut ctx).unwrap(), 3.0);
+}
+
+#[test]
+fn test_user_function_args_error() {
+	let mut ctx = Context::new();
+	execute("f(x) = x + 1", &mut ctx).unwrap();
+	assert_eq!(calculate("f(1, 2)", &mut ctx).unwrap_err().error_type, CalcErrorType::ArgumentError);
+}
+
+#[test]
+fn test_user_function_params_error() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("f(x, x) = x", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	assert_eq!(calculate("f(2) = 2", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+}
+
+#[test]
+fn test_user_function_recursion_error() {
+	let mut ctx = Context::new();
+	execute("f(x) = f(x + 1)", &mut ctx).unwrap();
+	assert_eq!(calculate("f(1)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+}
+
+#[test]
+fn test_evaluation_depth_small_stack() {
+	// debug builds use a lot of stack per level, test threads only get 2 MB
+	let thread = std::thread::Builder::new().stack_size(2 * 1024 * 1024).spawn(|| {
+		let mut ctx = Context::new();
+		execute("g(x) = x <= 0 ? 0 : 1 + g(x - 1)", &mut ctx).unwrap();
+		assert_eq!(calculate("g(300)", &mut ctx), Ok(300.0));
+		let error = calculate("g(2000)", &mut ctx).unwrap_err();
+		assert_eq!(error.msg.lines().next(), Some("Maximum call depth exceeded in \"g()\""));
+		execute("f(x) = x <= 0 ? 0 : 1 + 2*(3 + 4*(5 + 6*(7 + f(x - 1) - 7) - 5) - 3)", &mut ctx).unwrap();
+		assert!(calculate("f(300)", &mut ctx).is_ok());
+		let nested = format!("{}1{}", "sqrt(".repeat(1000), ")".repeat(1000));
+		assert_eq!(calculate(&nested, &mut ctx), Ok(1.0));
+	}).unwrap();
+	thread.join().unwrap();
+}
+
+#[test]
+fn test_long_expressions() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate(&vec!["1"; 300].join(" + "), &mut ctx), Ok(300.0));
+	assert_eq!(calculate(&vec!["2"; 300].join(" * "), &mut ctx), Ok(2f64.powi(300)));
+	execute("cnt(n) = n == 0 ? 0 : 1 + cnt(n - 1)", &mut ctx).unwrap();
+	assert_eq!(calculate("cnt(500)", &mut ctx), Ok(500.0));
+}
+
+#[test]
+fn test_calculate_definition() {
+	let mut ctx = Context::new();
+	assert!(calculate("f(x) = x^2", &mut ctx).unwrap().is_nan());
+	assert_eq!(calculate("f(3)", &mut ctx), Ok(9.0));
+}
+
+#[test]
+fn test_user_function_builtin_error() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("sin(x) = x", &mut ctx).unwrap_err().error_type, CalcErrorType::AssignmentError);
+	ctx.allow_builtin_override = true;
+	assert!(execute("sin(x) = x", &mut ctx).is_ok());
+	assert_eq!(calculate("sin(2)", &mut ctx).unwrap(), 2.0);
+}
+
+#[test]
+fn test_execute_definition() {
+	let mut ctx = Context::new();
+	assert_eq!(execute("f(x, y) = x * y", &mut ctx).unwrap(), Answer::Definition("f(x, y)".to_string()));
+	assert_eq!(execute("f(2, 3)", &mut ctx).unwrap(), Answer::Value(Value::Integer(6.into())));
+}
+
+#[test]
+fn test_registered_function() {
+	let mut ctx = Context::new();
+	let rates = [1.5, 2.5];
+	ctx.register_function("lookup_rate", Arity::Exact(1), move |args| {
+		match rates.get(args[0] as usize) {
+			Some(rate) => Ok(*rate),
+			None => Err(CalcError::new(CalcErrorType::ArgumentError, "Unknown rate")),
+		}
+	}).unwrap();
+	assert_eq!(calculate("100 * lookup_rate(1)", &mut ctx).unwrap(), 250.0);
+	assert_eq!(calculate("lookup_rate(5)", &mut ctx).unwrap_err().error_type, CalcErrorType::ArgumentError);
+}
+
+#[test]
+fn test_registered_function_protected() {
+	let mut ctx = Context::new();
+	ctx.register_function("g", Arity::AtLeast(0), |args| Ok(args.len() as f64)).unwrap();
+	assert_eq!(calculate("g(1, 2, 3)", &mut ctx).unwrap(), 3.0);
+	assert_eq!(calculate("g(x) = x", &mut ctx).unwrap_err().error_type, CalcErrorType::AssignmentError);
+}
+
+#[test]
+fn test_custom_constant() {
+	let mut ctx = Context::new();
+	ctx.define_constant("g", 9.80665).unwrap();
+	assert_eq!(calculate("g * 2", &mut ctx).unwrap(), 19.6133);
+	assert_eq!(calculate("g = 10", &mut ctx).unwrap_err().error_type, CalcErrorType::AssignmentError);
+}
+
+fn complex_ctx() -> Context {
+	let mut ctx = Context::new();
+	ctx.number_mode = NumberMode::Complex;
+	ctx
+}
+
+fn assert_complex(val: Value, re: f64, im: f64) {
+	let z = val.to_complex();
+	assert!((z.re - re).abs() < 1e-12 && (z.im - im).abs() < 1e-12, "{val} != {re}+{im}i");
+}
+
+#[test]
+fn test_complex_sqrt() {
+	let mut ctx = complex_ctx();
+	assert_eq!(execute("sqrt(-1)", &mut ctx).unwrap(), Answer::Value(Value::Complex(Complex64::i())));
+	assert!(calculate("sqrt(-1)", &mut Context::new()).unwrap().is_nan());
+}
+
+#[test]
+fn test_complex_arithmetic() {
+	let mut ctx = complex_ctx();
+	let _ = calculate("z = 3 + 4i", &mut ctx);
+	assert_eq!(execute("z", &mut ctx).unwrap(), Answer::Value(Value::Complex(Complex64::new(3.0, 4.0))));
+	assert_eq!(calculate("z * conj(z)", &mut ctx).unwrap(), 25.0);
+	assert_eq!(calculate("abs(z)", &mut ctx).unwrap(), 5.0);
+	assert_eq!(calculate("i^2", &mut ctx).unwrap(), -1.0);
+}
+
+#[test]
+fn test_complex_functions() {
+	let mut ctx = complex_ctx();
+	let Answer::Value(val) = execute("ln(-2)", &mut ctx).unwrap() else { panic!() };
+	assert_complex(val, 2f64.ln(), std::f64::consts::PI);
+	let Answer::Value(val) = execute("e^(i pi)", &mut ctx).unwrap() else { panic!() };
+	assert_complex(val, -1.0, 0.0);
+	assert_eq!(calculate("re(2 - 3i)", &mut ctx).unwrap(), 2.0);
+	assert_eq!(calculate("im(2 - 3i)", &mut ctx).unwrap(), -3.0);
+	assert_eq!(calculate("arg(i)", &mut ctx).unwrap(), std::f64::consts::FRAC_PI_2);
+}
+
+#[test]
+fn test_complex_factorial() {
+	let mut ctx = complex_ctx();
+	let Answer::Value(val) = execute("i!", &mut ctx).unwrap() else { panic!() };
+	assert_complex(val, 0.498015668118356, -0.154949828301811);
+}
+
+#[test]
+fn test_complex_result_error() {
+	let mut ctx = complex_ctx();
+	assert_eq!(calculate("sqrt(-4)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+}
+
+#[test]
+fn test_complex_i_constant() {
+	let mut ctx = complex_ctx();
+	assert_eq!(calculate("i = 2", &mut ctx).unwrap_err().error_type, CalcErrorType::AssignmentError);
+	let mut ctx = Context::new();
+	assert!(calculate("i = 2", &mut ctx).is_ok());
+}
+
+#[test]
+fn test_complex_argument_error() {
+	let mut ctx = complex_ctx();
+	assert_eq!(calculate("max(i, 1)", &mut ctx).unwrap_err().error_type, CalcErrorType::ArgumentError);
+}
+
+fn decimal_ctx() -> Context {
+	let mut ctx = Context::new();
+	ctx.number_mode = NumberMode::Decimal;
+	ctx
+}
+
+fn eval_decimal(input: &str, ctx: &mut Context) -> String {
+	let Answer::Value(val) = execute(input, ctx).unwrap() else { panic!() };
+	assert!(matches!(val, Value::Decimal(_)), "{val} is not a decimal");
+	val.to_string()
+}
+
+#[test]
+fn test_decimal_exact_literals() {
+	let mut ctx = decimal_ctx();
+	assert_eq!(eval_decimal("0.1 + 0.2", &mut ctx), "0.3");
+	assert_eq!(eval_decimal("1 - 0.9", &mut ctx), "0.1");
+	assert_eq!(calculate("0.1 + 0.2", &mut ctx).unwrap(), 0.3);
+}
+
+#[test]
+fn test_decimal_precision() {
+	let mut ctx = decimal_ctx();
+	ctx.precision = 20;
+	assert_eq!(eval_decimal("1/3", &mut ctx), "0.33333333333333333333");
+	assert_eq!(eval_decimal("pi", &mut ctx), "3.1415926535897932385");
+	ctx.precision = 40;
+	assert_eq!(eval_decimal("sqrt(2)", &mut ctx), "1.41421356237309504880168872420969807857");
+	assert_eq!(eval_decimal("e", &mut ctx), "2.718281828459045235360287471352662497757");
+}
+
+#[test]
+fn test_decimal_factorial() {
+	let mut ctx = decimal_ctx();
+	assert_eq!(eval_decimal("25!", &mut ctx), "15511210043330985984000000");
+	assert_eq!(eval_decimal("fac(5)", &mut ctx), "120");
+}
+
+#[test]
+fn test_decimal_functions() {
+	let mut ctx = decimal_ctx();
+	ctx.precision = 25;
+	assert_eq!(eval_decimal("ln(2)", &mut ctx), "0.6931471805599453094172321");
+	assert_eq!(eval_decimal("sin(pi/6)", &mut ctx), "0.5");
+	assert_eq!(eval_decimal("2^0.5", &mut ctx), "1.414213562373095048801689");
+	assert_eq!(eval_decimal("log(8, 2)", &mut ctx), "3");
+	assert_eq!(eval_decimal("round(2.5) + floor(-1.5)", &mut ctx), "1");
+	assert_eq!(eval_decimal("mean(1, 2, 4)", &mut ctx), "2.333333333333333333333333");
+}
+
+#[test]
+fn test_stddev_modes() {
+	let mut ctx = Context::new();
+	let expected = 1.25f64.sqrt();
+	for mode in [NumberMode::Real, NumberMode::Complex, NumberMode::Decimal] {
+		ctx.number_mode = mode;
+		let res = calculate("stddev(1, 2, 3, 4)", &mut ctx).unwrap();
+		assert!((res - expected).abs() < 1e-12, "{res} != {expected}");
+	}
+}
+
+#[test]
+fn test_decimal_errors() {
+	let mut ctx = decimal_ctx();
+	assert_eq!(calculate("1/0", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("ln(-1)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	// huge angles fail quickly rather than computing pi to thousands of digits
+	let start = std::time::Instant::now();
+	assert_eq!(calculate("sin(1e2000)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("cos(1e20000)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert!(calculate("tan(1e100)", &mut ctx).is_ok());
+	assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[test]
+fn test_decimal_mode_switch() {
+	let mut ctx = decimal_ctx();
+	execute("a = 0.1", &mut ctx).unwrap();
+	ctx.number_mode = NumberMode::Real;
+	assert_eq!(calculate("0.1 * 3", &mut ctx).unwrap(), 0.30000000000000004);
+	// decimals stored earlier stay exact
+	assert_eq!(eval_decimal("a * 3", &mut ctx), "0.3");
+}
+
+fn rational_ctx() -> Context {
+	let mut ctx = Context::new();
+	ctx.number_mode = NumberMode::Rational;
+	ctx
+}
+
+fn eval_rational(input: &str, ctx: &mut Context) -> String {
+	let Answer::Value(val) = execute(input, ctx).unwrap() else { panic!() };
+	assert!(matches!(val, Value::Rational(_)), "{val} is not a rational");
+	val.format(ctx)
+}
+
+#[test]
+fn test_rational_arithmetic() {
+	let mut ctx = rational_ctx();
+	assert_eq!(eval_rational("1/3 + 1/6", &mut ctx), "1/2");
+	assert_eq!(eval_rational("0.1 + 0.2", &mut ctx), "3/10");
+	assert_eq!(eval_rational("(2/3)^3", &mut ctx), "8/27");
+	assert_eq!(eval_rational("2^-2", &mut ctx), "1/4");
+	assert_eq!(eval_rational("7 // 2", &mut ctx), "3");
+	assert_eq!(eval_rational("7/2 % 1", &mut ctx), "1/2");
+	assert_eq!(eval_rational("30!", &mut ctx), "265252859812191058636308480000000");
+	assert_eq!(calculate("1/4 + 1/4", &mut ctx).unwrap(), 0.5);
+}
+
+#[test]
+fn test_rational_builtins() {
+	let mut ctx = rational_ctx();
+	assert_eq!(eval_rational("mean(1, 2)", &mut ctx), "3/2");
+	assert_eq!(eval_rational("abs(-2/3)", &mut ctx), "2/3");
+	assert_eq!(eval_rational("floor(7/2)", &mut ctx), "3");
+	assert_eq!(eval_rational("sqrt(4/9)", &mut ctx), "2/3");
+	assert_eq!(eval_rational("max(1/3, 2/5)", &mut ctx), "2/5");
+}
+
+#[test]
+fn test_rational_float_fallback() {
+	let mut ctx = rational_ctx();
+	assert_eq!(execute("sqrt(2)", &mut ctx).unwrap(), Answer::Value(Value::Real(std::f64::consts::SQRT_2)));
+	assert_eq!(execute("sin(0) + 1/2", &mut ctx).unwrap(), Answer::Value(Value::Real(0.5)));
+	assert_eq!(execute("4^0.5", &mut ctx).unwrap(), Answer::Value(Value::Real(2.0)));
+	assert_eq!(execute("0.5!", &mut ctx).unwrap(), Answer::Value(Value::Real(0.8862269254527583)));
+}
+
+#[test]
+fn test_rational_format() {
+	let mut ctx = rational_ctx();
+	assert_eq!(eval_rational("-7/4", &mut ctx), "-7/4");
+	ctx.rational_format = RationalFormat::Mixed;
+	assert_eq!(eval_rational("-7/4", &mut ctx), "-1 3/4");
+	ctx.rational_format = RationalFormat::Decimal;
+	assert_eq!(eval_rational("-7/4", &mut ctx), "-1.75");
+	ctx.precision = 5;
+	assert_eq!(eval_rational("1/3", &mut ctx), "0.33333");
+}
+
+#[test]
+fn test_rational_errors() {
+	let mut ctx = rational_ctx();
+	assert_eq!(calculate("1/0", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("0^-1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+}
+
+fn eval_integer(input: &str, ctx: &mut Context) -> String {
+	let Answer::Value(val) = execute(input, ctx).unwrap() else { panic!() };
+	assert!(matches!(val, Value::Integer(_)), "{val} is not an integer");
+	val.to_string()
+}
+
+#[test]
+fn test_exact_integers() {
+	let mut ctx = Context::new();
+	assert_eq!(eval_integer("30!", &mut ctx), "265252859812191058636308480000000");
+	assert_eq!(eval_integer("2^100", &mut ctx), "1267650600228229401496703205376");
+	assert_eq!(eval_integer("2^64 * 3 - 1", &mut ctx), "55340232221128654847");
+	assert_eq!(eval_integer("-7 // 2", &mut ctx), "-4");
+	assert_eq!(eval_integer("(10^20 + 3) % 10", &mut ctx), "3");
+	assert_eq!(eval_integer("12 / 4", &mut ctx), "3");
+	assert_eq!(eval_integer("171!", &mut ctx).len(), 310);
+	assert_eq!(calculate("171!", &mut ctx).unwrap(), f64::INFINITY);
+}
+
+#[test]
+fn test_exact_integer_builtins() {
+	let mut ctx = Context::new();
+	assert_eq!(eval_integer("fac(25)", &mut ctx), "15511210043330985984000000");
+	assert_eq!(eval_integer("abs(-2^70)", &mut ctx), "1180591620717411303424");
+	assert_eq!(eval_integer("max(3^40, 2^64)", &mut ctx), "18446744073709551616");
+	assert_eq!(eval_integer("sqrt(10^30)", &mut ctx), "1000000000000000");
+}
+
+#[test]
+fn test_exact_integer_float_fallback() {
+	let mut ctx = Context::new();
+	assert_eq!(execute("7 / 2", &mut ctx).unwrap(), Answer::Value(Value::Real(3.5)));
+	assert_eq!(execute("2^-1", &mut ctx).unwrap(), Answer::Value(Value::Real(0.5)));
+	assert_eq!(execute("2.0 * 3", &mut ctx).unwrap(), Answer::Value(Value::Real(6.0)));
+	assert_eq!(execute("mean(1, 2)", &mut ctx).unwrap(), Answer::Value(Value::Real(1.5)));
+	assert_eq!(execute("1 / 0", &mut ctx).unwrap(), Answer::Value(Value::Real(f64::INFINITY)));
+	ctx.exact_integers = false;
+	assert!(matches!(execute("30!", &mut ctx).unwrap(), Answer::Value(Value::Real(_))));
+}
+
+fn angle_ctx(mode: AngleMode) -> Context {
+	let mut ctx = Context::new();
+	ctx.angle_mode = mode;
+	ctx
+}
+
+#[test]
+fn test_angle_mode_degrees() {
+	let mut ctx = angle_ctx(AngleMode::Degrees);
+	assert_eq!(calculate("sin(30)", &mut ctx).unwrap(), 0.5);
+	assert_eq!(calculate("cos(60)", &mut ctx).unwrap(), 0.5);
+	assert_eq!(calculate("cos(90)", &mut ctx).unwrap(), 0.0);
+	assert_eq!(calculate("tan(45)", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("asin(1)", &mut ctx).unwrap(), 90.0);
+	assert_eq!(calculate("atan(1)", &mut ctx).unwrap(), 45.0);
+	assert_eq!(calculate("sinh(0)", &mut ctx).unwrap(), 0.0);
+}
+
+#[test]
+fn test_angle_mode_gradians() {
+	let mut ctx = angle_ctx(AngleMode::Gradians);
+	assert_eq!(calculate("sin(100)", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("acos(0)", &mut ctx).unwrap(), 100.0);
+	assert_eq!(calculate("90deg", &mut ctx).unwrap(), 100.0);
+}
+
+#[test]
+fn test_degree_literals() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("180°", &mut ctx).unwrap(), std::f64::consts::PI);
+	assert_eq!(calculate("90deg", &mut ctx).unwrap(), std::f64::consts::FRAC_PI_2);
+	assert_eq!(calculate("(45 + 45)°", &mut ctx).unwrap(), std::f64::consts::FRAC_PI_2);
+	assert_eq!(calculate("2 * 90° - pi", &mut ctx).unwrap(), 0.0);
+	// degree literals give the same results as in degree mode
+	assert_eq!(calculate("sin(30°)", &mut ctx).unwrap(), 0.5);
+	assert_eq!(calculate("cos(-60deg)", &mut ctx).unwrap(), 0.5);
+	assert_eq!(calculate("cos(90°)", &mut ctx).unwrap(), 0.0);
+	assert_eq!(eval_list("sin([30°, 150°])", &mut ctx), "[0.5, 0.5]");
+	assert!(calculate("sin(pi / 6)", &mut ctx).unwrap() != 0.5);
+	// `°C` isn't a unit
+	assert_eq!(calculate("10 °C", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	assert_eq!(calculate("20deg F", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	assert_eq!(calculate("2 * 30° x", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	assert_eq!(calculate("30° (2)", &mut ctx).unwrap(), std::f64::consts::PI / 3.0);
+	let mut ctx = angle_ctx(AngleMode::Degrees);
+	assert_eq!(calculate("30°", &mut ctx).unwrap(), 30.0);
+	assert_eq!(calculate("sin(30deg)", &mut ctx).unwrap(), 0.5);
+}
+
+#[test]
+fn test_angle_conversion_functions() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("deg(pi)", &mut ctx).unwrap(), 180.0);
+	assert_eq!(calculate("rad(180)", &mut ctx).unwrap(), std::f64::consts::PI);
+	// conversions don't depend on the angle mode
+	let mut ctx = angle_ctx(AngleMode::Gradians);
+	assert_eq!(calculate("deg(pi)", &mut ctx).unwrap(), 180.0);
+}
+
+#[test]
+fn test_angle_mode_decimal() {
+	let mut ctx = angle_ctx(AngleMode::Degrees);
+	ctx.number_mode = NumberMode::Decimal;
+	ctx.precision = 30;
+	let Answer::Value(val) = execute("asin(1)", &mut ctx).unwrap() else { panic!() };
+	assert_eq!(val.to_string(), "90");
+	let Answer::Value(val) = execute("rad(180)", &mut ctx).unwrap() else { panic!() };
+	assert_eq!(val.to_string(), "3.14159265358979323846264338328");
+}
+
+fn eval_quantity(input: &str, ctx: &mut Context) -> String {
+	let Answer::Value(val) = execute(input, ctx).unwrap() else { panic!() };
+	assert!(matches!(val, Value::Quantity(_)), "{val} is not a quantity");
+	val.to_string()
+}
+
+#[test]
+fn test_units_arithmetic() {
+	let mut ctx = Context::new();
+	assert_eq!(eval_quantity("5 m / 2 s", &mut ctx), "2.5 m/s");
+	assert_eq!(eval_quantity("2 kg * 9.81 m/s^2", &mut ctx), "19.62 N");
+	assert_eq!(eval_quantity("2 N * 3 m", &mut ctx), "6 J");
+	assert_eq!(eval_quantity("1 km + 500 m", &mut ctx), "1500 m");
+	assert_eq!(eval_quantity("sqrt(16 m^2)", &mut ctx), "4 m");
+	assert_eq!(calculate("5 m / 2 m", &mut ctx).unwrap(), 2.5);
+}
+
+#[test]
+fn test_units_conversion() {
+	let mut ctx = Context::new();
+	assert_eq!(eval_quantity("60 mph to m/s", &mut ctx), "26.8224 m/s");
+	assert_eq!(eval_quantity("1 kWh to MJ", &mut ctx), "3.6 MJ");
+	assert_eq!(eval_quantity("3 µs to ns", &mut ctx), "3000 ns");
+	assert_eq!(eval_quantity("90 min to h", &mut ctx), "1.5 h");
+	assert_eq!(calculate("2 km to m", &mut ctx).unwrap(), 2000.0);
+	assert_eq!(eval_quantity("60 mph to (m/s)", &mut ctx), "26.8224 m/s");
+	assert_eq!(eval_quantity("1 h to(min)", &mut ctx), "60 min");
+	// converted quantities keep their unit through additions
+	assert_eq!(eval_quantity("(1 km to km) + 500 m", &mut ctx), "1.5 km");
+}
+
+#[test]
+fn test_units_dimension_errors() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("3 m + 2 s", &mut ctx).unwrap_err().error_type, CalcErrorType::DimensionError);
+	assert_eq!(calculate("5 kg to m", &mut ctx).unwrap_err().error_type, CalcErrorType::DimensionError);
+	assert_eq!(calculate("sin(2 m)", &mut ctx).unwrap_err().error_type, CalcErrorType::DimensionError);
+	assert_eq!(calculate("2^(1 s)", &mut ctx).unwrap_err().error_type, CalcErrorType::DimensionError);
+	assert_eq!(calculate("1 + 1 m", &mut ctx).unwrap_err().error_type, CalcErrorType::DimensionError);
+	assert!(calculate("1 + 1 m", &mut ctx).unwrap_err().msg.starts_with("Incompatible units in dimensionless + m\n"));
+	assert!(calculate("5 to m", &mut ctx).unwrap_err().msg.starts_with("Can't convert dimensionless to m\n"));
+}
+
+#[test]
+fn test_units_shadowed_by_variables() {
+	let mut ctx = Context::new();
+	calculate("m = 3", &mut ctx).unwrap();
+	assert_eq!(calculate("2 m", &mut ctx).unwrap(), 6.0);
+	let mut ctx = Context::new();
+	ctx.units = false;
+	assert_eq!(calculate("2 m", &mut ctx).unwrap_err().error_type, CalcErrorType::UndefinedIdentifier);
+}
+
+fn eval_list(input: &str, ctx: &mut Context) -> String {
+	let Answer::Value(val) = execute(input, ctx).unwrap() else { panic!() };
+	assert!(matches!(val, Value::List(_)), "{val} is not a list");
+	val.to_string()
+}
+
+#[test]
+fn test_list_functions() {
+	let mut ctx = Context::new();
+	assert_eq!(eval_list("data = [1, 2, 3, 4]", &mut ctx), "[1, 2, 3, 4]");
+	assert_eq!(calculate("mean(data)", &mut ctx).unwrap(), 2.5);
+	assert_eq!(calculate("sum(data)", &mut ctx).unwrap(), 10.0);
+	assert_eq!(calculate("len(data)", &mut ctx).unwrap(), 4.0);
+	assert_eq!(eval_list("max(data, 3)", &mut ctx), "[3, 3, 3, 4]");
+	assert_eq!(calculate("len([])", &mut ctx).unwrap(), 0.0);
+	assert_eq!(calculate("len(5)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("len([1, 2], [3])", &mut ctx).unwrap_err().error_type, CalcErrorType::ArgumentError);
+	assert_eq!(eval_list("sqrt([1, 4, 9])", &mut ctx), "[1, 2, 3]");
+	assert_eq!(eval_list("root([8, 27], 3)", &mut ctx), "[2, 3]");
+}
+
+#[test]
+fn test_list_arithmetic() {
+	let mut ctx = Context::new();
+	assert_eq!(eval_list("[1, 2, 3] * 2", &mut ctx), "[2, 4, 6]");
+	assert_eq!(eval_list("10 - [1, 2]", &mut ctx), "[9, 8]");
+	assert_eq!(eval_list("[1, 2] + [3, 4]", &mut ctx), "[4, 6]");
+	assert_eq!(eval_list("-[1, 2]^2", &mut ctx), "[-1, -4]");
+	assert_eq!(eval_list("[3, 4]!", &mut ctx), "[6, 24]");
+	assert_eq!(eval_list("[1, 2] m", &mut ctx), "[1 m, 2 m]");
+	let err = calculate("[1, 2] + [1, 2, 3]", &mut ctx).unwrap_err();
+	assert_eq!(err.error_type, CalcErrorType::CalculationError);
+	// lists can't be returned as a single number
+	assert_eq!(calculate("[1, 2]", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+}
+
+#[test]
+fn test_list_indexing() {
+	let mut ctx = Context::new();
+	execute("data = [10, 20, 30, 40]", &mut ctx).unwrap();
+	assert_eq!(calculate("data[0]", &mut ctx).unwrap(), 10.0);
+	assert_eq!(calculate("data[-1]", &mut ctx).unwrap(), 40.0);
+	assert_eq!(calculate("[1, 2, 3][1]", &mut ctx).unwrap(), 2.0);
+	assert_eq!(calculate("2 data[1]", &mut ctx).unwrap(), 40.0);
+	assert_eq!(eval_list("data[1:3]", &mut ctx), "[20, 30]");
+	assert_eq!(eval_list("data[:2]", &mut ctx), "[10, 20]");
+	assert_eq!(eval_list("data[-2:]", &mut ctx), "[30, 40]");
+	assert_eq!(eval_list("data[3:10]", &mut ctx), "[40]");
+	assert_eq!(calculate("data[4]", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("data[0.5]", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("pi[0]", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+}
+
+#[test]
+fn test_list_decimal() {
+	let mut ctx = decimal_ctx();
+	assert_eq!(eval_list("[0.1, 0.2] + 0.1", &mut ctx), "[0.2, 0.3]");
+}
+
+#[test]
+fn test_matrix_arithmetic() {
+	let mut ctx = Context::new();
+	let Answer::Value(a) = execute("A = [[1, 2], [3, 4]]", &mut ctx).unwrap() else { panic!() };
+	assert!(matches!(a, Value::Matrix(_)));
+	let eval = |input: &str, ctx: &mut Context| {
+		let Answer::Value(val) = execute(input, ctx).unwrap() else { panic!() };
+		val.to_string()
+	};
+	assert_eq!(eval("A * A", &mut ctx), "[[7, 10], [15, 22]]");
+	assert_eq!(eval("A^2 - A * A", &mut ctx), "[[0, 0], [0, 0]]");
+	assert_eq!(eval("2A + 1", &mut ctx), "[[3, 5], [7, 9]]");
+	assert_eq!(eval("A * [1, 1]", &mut ctx), "[3, 7]");
+	assert_eq!(eval("transpose(A)", &mut ctx), "[[1, 3], [2, 4]]");
+	assert_eq!(eval("A[1]", &mut ctx), "[3, 4]");
+	assert_eq!(calculate("A[1][0]", &mut ctx).unwrap(), 3.0);
+	assert_eq!(calculate("sum(A)", &mut ctx).unwrap(), 10.0);
+}
+
+#[test]
+fn test_matrix_functions() {
+	let mut ctx = Context::new();
+	execute("A = [[1, 2], [3, 4]]", &mut ctx).unwrap();
+	assert_eq!(calculate("det(A)", &mut ctx).unwrap(), -2.0);
+	assert_eq!(calculate("trace(A)", &mut ctx).unwrap(), 5.0);
+	assert_eq!(calculate("rank([[1, 2], [2, 4]])", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("len([[1, 2, 3], [4, 5, 6]])", &mut ctx).unwrap(), 2.0);
+	assert_eq!(calculate("det(identity(3) * 2)", &mut ctx).unwrap(), 8.0);
+	let eval = |input: &str, ctx: &mut Context| {
+		let Answer::Value(val) = execute(input, ctx).unwrap() else { panic!() };
+		val.to_string()
+	};
+	assert_eq!(eval("A[len(A) - 1]", &mut ctx), "[3, 4]");
+	assert_eq!(eval("inv(A)", &mut ctx), "[[-2, 1], [1.5, -0.5]]");
+	assert_eq!(eval("A * inv(A)", &mut ctx), "[[1, 0], [0, 1]]");
+	assert_eq!(eval("solve(A, [5, 11])", &mut ctx), "[1, 2]");
+	assert_eq!(eval("zeros(2, 3)", &mut ctx), "[[0, 0, 0], [0, 0, 0]]");
+	assert_eq!(eval("eig([[2, 1], [1, 2]])", &mut ctx), "[1, 3]");
+	let Answer::Value(Value::List(eigenvalues)) = execute("eig([[2, 0, 0], [0, 3, 4], [0, 4, 9]])", &mut ctx).unwrap() else { panic!() };
+	for (val, expected) in eigenvalues.iter().zip([1.0, 2.0, 11.0]) {
+		assert!((val.to_f64().unwrap() - expected).abs() < 1e-12, "{val} != {expected}");
+	}
+}
+
+#[test]
+fn test_matrix_errors() {
+	let mut ctx = Context::new();
+	let error_type = |input: &str, ctx: &mut Context| calculate(input, ctx).unwrap_err().error_type;
+	assert_eq!(error_type("inv([[1, 2], [2, 4]])", &mut ctx), CalcErrorType::MatrixError);
+	assert_eq!(error_type("[[1, 2], [3, 4]] * [[1, 2, 3]]", &mut ctx), CalcErrorType::MatrixError);
+	assert_eq!(error_type("[[1, 2]] + [[1], [2]]", &mut ctx), CalcErrorType::MatrixError);
+	assert_eq!(error_type("det([[1, 2, 3], [4, 5, 6]])", &mut ctx), CalcErrorType::MatrixError);
+	assert_eq!(error_type("[[1, 2], [3]]", &mut ctx), CalcErrorType::MatrixError);
+	assert_eq!(error_type("eig([[1, 2], [3, 4]])", &mut ctx), CalcErrorType::MatrixError);
+	assert_eq!(error_type("solve([[1, 2], [2, 4]], [1, 2])", &mut ctx), CalcErrorType::MatrixError);
+	assert_eq!(error_type("identity(0)", &mut ctx), CalcErrorType::ArgumentError);
+}
+
+#[test]
+fn test_comparison() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("1 < 2", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("2 <= 1", &mut ctx).unwrap(), 0.0);
+	assert_eq!(calculate("3 > 3", &mut ctx).unwrap(), 0.0);
+	assert_eq!(calculate("3 >= 3", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("1 + 1 == 2", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("3! != 6", &mut ctx).unwrap(), 0.0);
+	// integers are compared exactly
+	assert_eq!(calculate("2^64 + 1 > 2^64", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("1 km > 500 m", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("1 m > 1 s", &mut ctx).unwrap_err().error_type, CalcErrorType::DimensionError);
+	assert_eq!(calculate("1 < 2 < 3", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+}
+
+#[test]
+fn test_logical_operators() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("1 < 2 && 2 < 3", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("1 > 2 || 2 > 3", &mut ctx).unwrap(), 0.0);
+	assert_eq!(calculate("2 && -1", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("!0", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("!(1 == 1)", &mut ctx).unwrap(), 0.0);
+	// `&&` binds tighter than `||`
+	assert_eq!(calculate("1 || 0 && 0", &mut ctx).unwrap(), 1.0);
+	// comparisons bind looser than arithmetic
+	assert_eq!(calculate("x = 2 + 2 == 4", &mut ctx).unwrap(), 1.0);
+	// the right-hand side is only evaluated when needed
+	assert_eq!(calculate("0 && undefined", &mut ctx).unwrap(), 0.0);
+	assert_eq!(calculate("1 || undefined", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("1 && undefined", &mut ctx).unwrap_err().error_type, CalcErrorType::UndefinedIdentifier);
+}
+
+#[test]
+fn test_comparison_modes() {
+	let mut ctx = rational_ctx();
+	assert_eq!(eval_rational("0.1 + 0.2 == 0.3", &mut ctx), "1");
+	let mut ctx = Context::new();
+	ctx.number_mode = NumberMode::Complex;
+	assert_eq!(calculate("i^2 == -1", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("i < 1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	let mut ctx = Context::new();
+	assert_eq!(eval_list("[1, 2, 3] >= 2", &mut ctx), "[0, 1, 1]");
+	assert_eq!(eval_list("![0, 2]", &mut ctx), "[1, 0]");
+}
+
+#[test]
+fn test_conditional() {
+	let mut ctx = Context::new();
+	execute("f(x) = if(x == 0, 1, sin(x) / x)", &mut ctx).unwrap();
+	assert_eq!(calculate("f(0)", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("f(pi / 2)", &mut ctx).unwrap(), 2.0 / std::f64::consts::PI);
+	assert_eq!(calculate("1 < 2 ? 10 : 20", &mut ctx).unwrap(), 10.0);
+	// conditionals are right-associative
+	assert_eq!(calculate("0 ? 1 : 0 ? 2 : 3", &mut ctx).unwrap(), 3.0);
+	assert_eq!(calculate("2 + (0 ? 1 : 2) * 3", &mut ctx).unwrap(), 8.0);
+	assert_eq!(calculate("if(1, 2)", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	assert_eq!(calculate("if([1, 0], 1, 2)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+}
+
+#[test]
+fn test_conditional_lazy() {
+	let mut ctx = Context::new();
+	// only the branch that's taken is evaluated
+	assert_eq!(calculate("1 ? 2 : undefined", &mut ctx).unwrap(), 2.0);
+	assert_eq!(calculate("if(0, 1 / [1, 2, 3][5], 4)", &mut ctx).unwrap(), 4.0);
+	execute("fac2(n) = n <= 1 ? 1 : n * fac2(n - 1)", &mut ctx).unwrap();
+	assert_eq!(calculate("fac2(10)", &mut ctx).unwrap(), 3628800.0);
+}
+
+#[test]
+fn test_piecewise() {
+	let mut ctx = Context::new();
+	execute("tax(i) = piecewise(i <= 10000, 0, i <= 40000, (i - 10000) / 5, 6000 + (i - 40000) * 2 / 5)", &mut ctx).unwrap();
+	assert_eq!(calculate("tax(5000)", &mut ctx).unwrap(), 0.0);
+	assert_eq!(calculate("tax(20000)", &mut ctx).unwrap(), 2000.0);
+	assert_eq!(calculate("tax(50000)", &mut ctx).unwrap(), 10000.0);
+	assert_eq!(calculate("piecewise(0, 1, 1, 2)", &mut ctx).unwrap(), 2.0);
+	assert_eq!(calculate("piecewise(0, 1)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("piecewise(1)", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	// slices still accept conditionals in parentheses
+	let Answer::Value(val) = execute("[1, 2, 3][(1 ? 1 : 0):]", &mut ctx).unwrap() else { panic!() };
+	assert_eq!(val.to_string(), "[2, 3]");
+}
+
+#[test]
+fn test_base_literals() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("0xFF", &mut ctx).unwrap(), 255.0);
+	assert_eq!(calculate("0xff + 1", &mut ctx).unwrap(), 256.0);
+	assert_eq!(calculate("0b1010", &mut ctx).unwrap(), 10.0);
+	assert_eq!(calculate("0o755", &mut ctx).unwrap(), 493.0);
+	assert_eq!(eval_integer("0xFFFFFFFFFFFFFFFFFFFF", &mut ctx), "1208925819614629174706175");
+	// `0b` without binary digits is still an implicit multiplication
+	calculate("b = 3", &mut ctx).unwrap();
+	assert_eq!(calculate("2b", &mut ctx).unwrap(), 6.0);
+}
+
+#[test]
+fn test_bitwise_operators() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("0xF0 & 0x3C", &mut ctx).unwrap(), 48.0);
+	assert_eq!(calculate("0b1100 | 0b0011", &mut ctx).unwrap(), 15.0);
+	assert_eq!(calculate("6 xor 3", &mut ctx).unwrap(), 5.0);
+	assert_eq!(calculate("0xF0 xor (0x0F)", &mut ctx).unwrap(), 255.0);
+	// names that merely start with a keyword are still functions
+	execute("xoring(a) = 2a", &mut ctx).unwrap();
+	execute("tox(a) = a + 1", &mut ctx).unwrap();
+	assert_eq!(calculate("xoring(2) + tox (1)", &mut ctx).unwrap(), 6.0);
+	assert_eq!(calculate("~5", &mut ctx).unwrap(), -6.0);
+	assert_eq!(calculate("1 << 10", &mut ctx).unwrap(), 1024.0);
+	assert_eq!(calculate("-256 >> 4", &mut ctx).unwrap(), -16.0);
+	// shifts bind looser than arithmetic, then come `&`, `xor` and `|`
+	assert_eq!(calculate("1 << 2 + 1", &mut ctx).unwrap(), 8.0);
+	assert_eq!(calculate("1 | 6 & 3", &mut ctx).unwrap(), 3.0);
+	assert_eq!(calculate("1 | 2 xor 3", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("1 | 2 == 3", &mut ctx).unwrap(), 1.0);
+	assert_eq!(calculate("2^64 & 1", &mut ctx).unwrap(), 0.0);
+	assert_eq!(eval_list("[1, 2, 3] << 1", &mut ctx), "[2, 4, 6]");
+}
+
+#[test]
+fn test_bitwise_errors() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("1.5 & 1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("~0.5", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("1 << -1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("1 << 2^40", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("1 m | 1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	// whole numbers of other kinds are accepted
+	assert_eq!(calculate("4.0 | 1", &mut ctx).unwrap(), 5.0);
+	let mut ctx = rational_ctx();
+	assert_eq!(eval_rational("0xF xor 4/2", &mut ctx), "13");
+}
+
+fn programmer_ctx(int_type: IntType, overflow_mode: OverflowMode) -> Context {
+	let mut ctx = Context::new();
+	ctx.number_mode = NumberMode::Programmer;
+	ctx.int_type = int_type;
+	ctx.overflow_mode = overflow_mode;
+	ctx
+}
+
+fn eval_programmer(input: &str, ctx: &mut Context) -> Value {
+	match execute(input, ctx).unwrap() {
+		Answer::Value(val) => val,
+		Answer::Definition(signature) => panic!("Expected a value, got {signature}"),
+	}
+}
+
+#[test]
+fn test_programmer_wrapping() {
+	let mut ctx = programmer_ctx(IntType::U8, OverflowMode::Wrap);
+	assert_eq!(eval_programmer("0xFF + 1", &mut ctx), Value::Integer(0.into()));
+	assert_eq!(eval_programmer("0 - 1", &mut ctx), Value::Integer(255.into()));
+	assert_eq!(eval_programmer("16 * 17", &mut ctx), Value::Integer(16.into()));
+	assert_eq!(eval_programmer("300", &mut ctx), Value::Integer(44.into()));
+	assert_eq!(eval_programmer("~0", &mut ctx), Value::Integer(255.into()));
+	assert_eq!(eval_programmer("1 << 8", &mut ctx), Value::Integer(0.into()));
+	assert_eq!(eval_programmer("3^5", &mut ctx), Value::Integer(243.into()));
+	assert_eq!(eval_programmer("3^6", &mut ctx), Value::Integer(217.into()));
+	let mut ctx = programmer_ctx(IntType::I8, OverflowMode::Wrap);
+	assert_eq!(eval_programmer("127 + 1", &mut ctx), Value::Integer((-128).into()));
+	assert_eq!(eval_programmer("0xFF", &mut ctx), Value::Integer((-1).into()));
+	assert_eq!(eval_programmer("-128 / -1", &mut ctx), Value::Integer((-128).into()));
+	let mut ctx = programmer_ctx(IntType::U64, OverflowMode::Wrap);
+	assert_eq!(eval_programmer("2^64 + 5", &mut ctx), Value::Integer(5.into()));
+	assert_eq!(eval_programmer("1000!", &mut ctx), Value::Integer(0.into()));
+}
+
+#[test]
+fn test_programmer_overflow() {
+	let mut ctx = programmer_ctx(IntType::U8, OverflowMode::Error);
+	assert_eq!(calculate("0xFF + 1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("0 - 1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("-1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("256", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("2^1000", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("6!", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("0xFF + 0", &mut ctx), Ok(255.0));
+	assert_eq!(calculate("1^255", &mut ctx), Ok(1.0));
+	// bitwise operations wrap around rather than overflowing
+	assert_eq!(calculate("~1", &mut ctx), Ok(254.0));
+	assert_eq!(calculate("0x81 << 1", &mut ctx), Ok(2.0));
+	let mut ctx = programmer_ctx(IntType::I8, OverflowMode::Error);
+	assert_eq!(calculate("-128", &mut ctx), Ok(-128.0));
+	assert_eq!(calculate("-128 - 1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("-(-128)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("(-2)^7", &mut ctx), Ok(-128.0));
+}
+
+#[test]
+fn test_programmer_arithmetic() {
+	let mut ctx = programmer_ctx(IntType::I32, OverflowMode::Error);
+	// division truncates towards zero, `//` rounds down
+	assert_eq!(calculate("7 / 2", &mut ctx), Ok(3.0));
+	assert_eq!(calculate("-7 / 2", &mut ctx), Ok(-3.0));
+	assert_eq!(calculate("-7 // 2", &mut ctx), Ok(-4.0));
+	assert_eq!(calculate("-7 % 2", &mut ctx), Ok(-1.0));
+	assert_eq!(calculate("-16 >> 2", &mut ctx), Ok(-4.0));
+	assert_eq!(calculate("1e3", &mut ctx), Ok(1000.0));
+	assert_eq!(calculate("3 < 4", &mut ctx), Ok(1.0));
+	assert_eq!(calculate("sqrt(49) + abs(-2) + max(3, 9)", &mut ctx), Ok(18.0));
+	assert_eq!(eval_programmer("[1, 2] * 3", &mut ctx), Value::List(vec![Value::Integer(3.into()), Value::Integer(6.into())]));
+	assert_eq!(calculate("1 / 0", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("1.5", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("2^-1", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("sqrt(2)", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+	assert_eq!(calculate("2 pi", &mut ctx).unwrap_err().error_type, CalcErrorType::CalculationError);
+}
+
+#[test]
+fn test_multiple_statements() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("a = 4; b = a + 1; a * b", &mut ctx), Ok(20.0));
+	assert_eq!(calculate("f(x) = 2x\nf(b)", &mut ctx), Ok(10.0));
+	assert_eq!(calculate("1;;2;", &mut ctx), Ok(2.0));
+	assert_eq!(calculate("ans + 1\n\n", &mut ctx), Ok(3.0));
+	assert_eq!(calculate(" ; ", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	// statements before a failing one are still applied
+	assert_eq!(calculate("c = 1; nothing + 1; c = 2", &mut ctx).unwrap_err().error_type, CalcErrorType::UndefinedIdentifier);
+	assert_eq!(calculate("c", &mut ctx), Ok(1.0));
+}
+
+#[test]
+fn test_calculate_script() {
+	let mut ctx = Context::new();
+	let answers = calculate_script("a = 3\r\nf(x) = x + a; f(2)\n\n3 +", &mut ctx).unwrap_err().answers;
+	assert_eq!(answers, vec![
+		Answer::Value(Value::Integer(3.into())),
+		Answer::Definition("f(x)".to_string()),
+		Answer::Value(Value::Integer(5.into())),
+	]);
+	assert_eq!(calculate_script("", &mut ctx), Ok(Vec::new()));
+	assert_eq!(calculate_script("1; 2", &mut ctx).unwrap().len(), 2);
+}
+
+#[test]
+fn test_script_error_location() {
+	let mut ctx = Context::new();
+	let error = calculate_script("a = 1\nb = a; c = b +\nd = 4", &mut ctx).unwrap_err();
+	assert_eq!((error.line, error.column), (2, 8));
+	assert_eq!(error.error.error_type, CalcErrorType::ParserError);
+	assert_eq!(error.answers.len(), 2);
+	let error = calculate_script("1\n  2 / x", &mut ctx).unwrap_err();
+	assert_eq!((error.line, error.column), (2, 3));
+	assert_eq!(error.error.error_type, CalcErrorType::UndefinedIdentifier);
+	assert!(error.to_string().starts_with("Line 2, column 3: "));
+	// columns count characters rather than bytes
+	let error = calculate_script("x = 30°; y", &mut ctx).unwrap_err();
+	assert_eq!((error.line, error.column), (1, 10));
+}
+
+#[test]
+fn test_comments() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("7 // 2 # floor division, not a comment", &mut ctx), Ok(3.0));
+	assert_eq!(calculate("a = 5 # radius; a = 6", &mut ctx), Ok(5.0));
+	assert_eq!(calculate("a#", &mut ctx), Ok(5.0));
+	assert_eq!(calculate("# nothing to calculate", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	let script = "# circle\nr = 2 # in m\n\n  # area\npi r^2; # done";
+	let answers = calculate_script(script, &mut ctx).unwrap();
+	assert_eq!(answers.len(), 2);
+	// a `;` inside of a comment doesn't start a statement
+	assert_eq!(calculate_script("# header\nr = 1 # c; x", &mut ctx).unwrap().len(), 1);
+}
+
+#[test]
+fn test_compound_assignment() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("a = 6", &mut ctx), Ok(6.0));
+	assert_eq!(calculate("a += 4", &mut ctx), Ok(10.0));
+	assert_eq!(calculate("a -= 1", &mut ctx), Ok(9.0));
+	assert_eq!(calculate("a *= 2", &mut ctx), Ok(18.0));
+	assert_eq!(calculate("a %= 5", &mut ctx), Ok(3.0));
+	assert_eq!(calculate("a ^= 2", &mut ctx), Ok(9.0));
+	assert_eq!(calculate("a /= 2", &mut ctx), Ok(4.5));
+	assert_eq!(calculate("a", &mut ctx), Ok(4.5));
+	// the right-hand side is a whole expression
+	assert_eq!(calculate("a += a > 4 ? 1 : 2", &mut ctx), Ok(5.5));
+	let mut ctx = rational_ctx();
+	execute("x = 1/3", &mut ctx).unwrap();
+	assert_eq!(eval_rational("x += 1/6", &mut ctx), "1/2");
+}
+
+#[test]
+fn test_compound_assignment_errors() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("b += 1", &mut ctx).unwrap_err().error_type, CalcErrorType::UndefinedIdentifier);
+	assert_eq!(calculate("pi *= 2", &mut ctx).unwrap_err().error_type, CalcErrorType::AssignmentError);
+	assert_eq!(calculate("pi", &mut ctx), Ok(std::f64::consts::PI));
+	// units aren't variables
+	assert_eq!(calculate("m += 1", &mut ctx).unwrap_err().error_type, CalcErrorType::UndefinedIdentifier);
+	assert_eq!(calculate("f(x) += 1", &mut ctx).unwrap_err().error_type, CalcErrorType::ParserError);
+	ctx.define_constant("g", 9.8).unwrap();
+	assert_eq!(calculate("g -= 1", &mut ctx).unwrap_err().error_type, CalcErrorType::AssignmentError);
+}
+
+fn error_span(input: &str, ctx: &mut Context) -> Option<(usize, usize)> {
+	calculate(input, ctx).unwrap_err().span.map(|span| (span.start, span.end))
+}
+
+#[test]
+fn test_error_spans() {
+	let mut ctx = Context::new();
+	assert_eq!(error_span("2 + x * 3", &mut ctx), Some((4, 5)));
+	assert_eq!(error_span("1 + sqrt(4, 2)", &mut ctx), Some((4, 14)));
+	assert_eq!(error_span("nope(1) + 1", &mut ctx), Some((0, 7)));
+	assert_eq!(error_span("2 * (1 m + 1 s)", &mut ctx), Some((5, 14)));
+	assert_eq!(error_span("[1, 2][5]", &mut ctx), Some((0, 9)));
+	assert_eq!(error_span("undefined += 1", &mut ctx), Some((0, 9)));
+	// errors in the body of a function are located at the call
+	execute("f(x) = x + y", &mut ctx).unwrap();
+	assert_eq!(error_span("3 * f(2)", &mut ctx), Some((4, 8)));
+	// assignments fail as a whole
+	assert_eq!(error_span("pi = 3", &mut ctx), None);
+	let mut ctx = decimal_ctx();
+	assert_eq!(error_span("1 + ln(0)", &mut ctx), Some((4, 9)));
+}
+
+#[test]
+fn test_error_span_rendering() {
+	let mut ctx = Context::new();
+	let error = calculate("2 + sqrt(4, 2)", &mut ctx).unwrap_err();
+	assert_eq!(error.msg, "Invalid number of arguments, expected 1\n| 2 + sqrt(4, 2)\n|     └────────┘ here");
+	let error = calculate("30° + x", &mut ctx).unwrap_err();
+	assert_eq!(error.msg, "Unknown variable \"x\"\n| 30° + x\n|       └── here");
+	let error = calculate("5 + * 5", &mut ctx).unwrap_err();
+	assert_eq!(error.span, Some(Span { start: 4, end: 5 }));
+	assert!(error.msg.ends_with("|     └── here"));
+}
+
+#[test]
+fn test_script_error_spans() {
+	let mut ctx = Context::new();
+	// spans are relative to the whole input
+	assert_eq!(error_span("a = 1; a + b", &mut ctx), Some((11, 12)));
+	let error = calculate_script("a = 1\n\n2 * sqrt(1, 2)", &mut ctx).unwrap_err();
+	assert_eq!(error.error.span, Some(Span { start: 11, end: 21 }));
+	assert_eq!((error.line, error.column), (3, 1));
+}
+
+fn parser_message(input: &str, ctx: &mut Context) -> String {
+	let error = calculate(input, ctx).unwrap_err();
+	assert_eq!(error.error_type, CalcErrorType::ParserError);
+	error.msg.lines().next().unwrap().to_string()
+}
+
+#[test]
+fn test_parser_error_messages() {
+	let mut ctx = Context::new();
+	assert_eq!(parser_message("5 +", &mut ctx), "Unexpected end of input, expected a number, an identifier, '(' or '[' after '+'");
+	assert_eq!(parser_message("5 + * 5", &mut ctx), "Unexpected '*', expected a number, an identifier, '(' or '[' after '+'");
+	assert_eq!(parser_message("5 5", &mut ctx), "Unexpected '5', expected an identifier, an operator, '(', '[' or '?' after '5'");
+	assert_eq!(parser_message("a +=", &mut ctx), "Unexpected end of input, expected a number, an identifier, '(' or '[' after '+='");
+	assert_eq!(parser_message("3 @ 4", &mut ctx), "Invalid token '@'");
+	let error = calculate("5 +", &mut ctx).unwrap_err();
+	assert_eq!(error.span, Some(Span { start: 3, end: 3 }));
+	assert!(error.expected.contains(&"number".to_string()));
+	assert!(error.expected.contains(&"(".to_string()));
+	assert!(!error.expected.contains(&"*".to_string()));
+	// only parser errors have expectations
+	assert!(calculate("1 + x", &mut ctx).unwrap_err().expected.is_empty());
+}
+
+#[test]
+fn test_suggestions() {
+	let mut ctx = Context::new();
+	let error = calculate("sqr(4)", &mut ctx).unwrap_err();
+	assert_eq!(error.error_type, CalcErrorType::UndefinedIdentifier);
+	assert_eq!(error.suggestions, vec!["sqrt"]);
+	assert!(error.msg.starts_with("Unknown function \"sqr()\", did you mean \"sqrt()\"?"));
+	let error = calculate("2 Pi", &mut ctx).unwrap_err();
+	assert_eq!(error.suggestions, vec!["pi"]);
+	assert!(error.msg.starts_with("Unknown variable \"Pi\", did you mean \"pi\"?"));
+	assert_eq!(calculate("tna(1)", &mut ctx).unwrap_err().suggestions, vec!["tan"]);
+	assert_eq!(calculate("sinn(1)", &mut ctx).unwrap_err().suggestions, vec!["sin", "sinh"]);
+	// user-defined names and function arguments are suggested too
+	execute("radius = 2", &mut ctx).unwrap();
+	assert_eq!(calculate("raduis * 2", &mut ctx).unwrap_err().suggestions, vec!["radius"]);
+	execute("area(width) = widht^2", &mut ctx).unwrap();
+	assert_eq!(calculate("area(2)", &mut ctx).unwrap_err().suggestions, vec!["width"]);
+	assert_eq!(calculate("aera(2)", &mut ctx).unwrap_err().suggestions, vec!["area"]);
+	assert_eq!(calculate("radius += nope", &mut ctx).unwrap_err().suggestions, Vec::<String>::new());
+	// short names are too close to everything else
+	let error = calculate("2 + x", &mut ctx).unwrap_err();
+	assert!(error.suggestions.is_empty());
+	assert!(error.msg.starts_with("Unknown variable \"x\"\n"));
+}
+
+#[test]
+fn test_unbalanced_brackets() {
+	let mut ctx = Context::new();
+	let error = calculate("2 * (3 + sqrt(4)", &mut ctx).unwrap_err();
+	assert_eq!(error.msg, "Unclosed '('\n| 2 * (3 + sqrt(4)\n|     └── here");
+	assert_eq!(error.span, Some(Span { start: 4, end: 5 }));
+	let error = calculate("(1 + 2)) * 3", &mut ctx).unwrap_err();
+	assert_eq!(error.msg, "Unmatched ')'\n| (1 + 2)) * 3\n|        └── here");
+	assert_eq!(parser_message("[1, 2", &mut ctx), "Unclosed '['");
+	assert_eq!(parser_message("[1, 2)", &mut ctx), "Unmatched ')'");
+	// brackets in comments don't count
+	assert_eq!(parser_message("1 + # (", &mut ctx), "Unexpected end of input, expected a number, an identifier, '(' or '[' after '+'");
+}
+
+fn strict_ctx() -> Context {
+	let mut ctx = Context::new();
+	ctx.strict_numerics = true;
+	ctx
+}
+
+fn strict_message(input: &str, ctx: &mut Context) -> String {
+	let error = calculate(input, ctx).unwrap_err();
+	assert_eq!(error.error_type, CalcErrorType::CalculationError);
+	error.msg.lines().next().unwrap().to_string()
+}
+
+#[test]
+fn test_non_strict_numerics() {
+	let mut ctx = Context::new();
+	assert_eq!(calculate("1/0", &mut ctx), Ok(f64::INFINITY));
+	assert!(calculate("sqrt(-1)", &mut ctx).unwrap().is_nan());
+	assert_eq!(calculate("log(0, 10)", &mut ctx), Ok(f64::NEG_INFINITY));
+	assert_eq!(calculate("1e400", &mut ctx), Ok(f64::INFINITY));
+}
+
+#[test]
+fn test_strict_numerics() {
+	let mut ctx = strict_ctx();
+	assert_eq!(strict_message("1/0", &mut ctx), "Division by zero in 1 / 0");
+	assert_eq!(strict_message("0/0", &mut ctx), "Division by zero in 0 / 0");
+	assert_eq!(strict_message("5 % 0", &mut ctx), "Division by zero in 5 % 0");
+	assert_eq!(strict_message("0^-1", &mut ctx), "Division by zero in 0 ^ -1");
+	assert_eq!(strict_message("10.5^400", &mut ctx), "10.5 ^ 400 overflows to infinity");
+	assert_eq!(strict_message("sqrt(-1)", &mut ctx), "The argument -1 is outside the domain of \"sqrt()\"");
+	assert_eq!(strict_message("asin(2)", &mut ctx), "The argument 2 is outside the domain of \"asin()\"");
+	assert_eq!(strict_message("log(0, 10)", &mut ctx), "\"log()\" is infinite for the arguments 0, 10");
+	assert_eq!(strict_message("sinh(1000)", &mut ctx), "\"sinh()\" overflows to infinity for the argument 1000");
+	assert_eq!(strict_message("(-1)!", &mut ctx), "Can't take the factorial of the negative integer -1");
+	assert_eq!(strict_message("171.5!", &mut ctx), "171.5! overflows to infinity");
+	assert_eq!(strict_message("[1, 0] / 0", &mut ctx), "Division by zero in 1 / 0");
+	assert_eq!(strict_message("1 m / 0", &mut ctx), "Division by zero in 1 m / 0");
+	assert_eq!(strict_message("2.5e308", &mut ctx), "2.5e308 overflows to infinity");
+	assert_eq!(strict_message("1 + 1e400", &mut ctx), "1e400 overflows to infinity");
+	// very large and very small numbers are written with an exponent
+	assert_eq!(strict_message("1e308 * 10", &mut ctx), "1e308 * 10 overflows to infinity");
+	assert_eq!(strict_message("1 / 1e-320", &mut ctx), "1 / 1e-320 overflows to infinity");
+	assert_eq!(strict_message("sinh(1e20)", &mut ctx), "\"sinh()\" overflows to infinity for the argument 1e20");
+	// errors are located at the offending operation or call
+	assert_eq!(error_span("2 + sqrt(-4)", &mut ctx), Some((4, 12)));
+	assert_eq!(strict_message("inf - inf", &mut ctx), "inf - inf is undefined");
+	assert_eq!(strict_message("0 * inf", &mut ctx), "0 * inf is undefined");
+	assert_eq!(strict_message("sin(inf)", &mut ctx), "The argument inf is outside the domain of \"sin()\"");
+	assert_eq!(strict_message("nan + 1", &mut ctx), "\"nan\" is not a number");
+	// finite results and explicit infinities are fine
+	assert_eq!(calculate("sqrt(4) + 1/4", &mut ctx), Ok(2.25));
+	assert_eq!(calculate("(-2.5)!", &mut ctx), calculate("fac(-2.5)", &mut ctx));
+	assert_eq!(calculate("inf + 1", &mut ctx), Ok(f64::INFINITY));
+}
+
+#[test]
+fn test_strict_numerics_complex() {
+	let mut ctx = complex_ctx();
+	ctx.strict_numerics = true;
+	assert_eq!(execute("sqrt(-1)", &mut ctx).unwrap(), Answer::Value(Value::Complex(Complex64::i())));
+	assert_eq!(strict_message("ln(0)", &mut ctx), "\"ln()\" is infinite for the argument 0");
+	assert_eq!(strict_message("1/0", &mut ctx), "Division by zero in 1 / 0");
+}